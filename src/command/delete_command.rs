@@ -0,0 +1,58 @@
+use crate::command::{find_workflow, DeleteCommand, HandleCommand};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::repository::WorkflowRepository;
+use crate::workflow::source::WorkflowId;
+use di::ServiceProvider;
+use std::process::ExitCode;
+
+impl DeleteCommand {
+    /// Remove the workflow and its file, returning the id it had
+    fn delete(&self, repo: &mut dyn WorkflowRepository) -> WorkflowResult<WorkflowId> {
+        let workflow = find_workflow(repo, &self.workflow)?;
+        let id = workflow
+            .id()
+            .cloned()
+            .ok_or_else(|| WorkflowError::NotFound(format!("file of {}", workflow.name)))?;
+        repo.delete_workflow(&id)?;
+        Ok(id)
+    }
+}
+
+impl HandleCommand for DeleteCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+
+        let id = self.delete(&mut *repo.borrow_mut())?;
+        eprintln!("deleted {}", id);
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::DeleteCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::repository::WorkflowRepository;
+    use crate::workflow::source::WorkflowId;
+    use std::path::Path;
+
+    #[test]
+    fn test_delete_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::copy(
+            Path::new("tests/fixtures/workflows/sample.yaml"),
+            dir.path().join("sample.yaml"),
+        )
+        .unwrap();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+        let command = DeleteCommand {
+            workflow: "Attach a header to an HTTP request with cURL".to_string(),
+        };
+
+        let id = command.delete(&mut repo).unwrap();
+        assert_eq!(id, WorkflowId::new("local", "sample.yaml"));
+        assert!(!dir.path().join("sample.yaml").exists());
+        assert!(repo.get_workflows().unwrap().is_empty());
+        assert!(command.delete(&mut repo).is_err());
+    }
+}
@@ -0,0 +1,263 @@
+use crate::command::{find_workflow, EditCommand, HandleCommand};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::Workflow;
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::io::{stderr, stdin, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::process::{Command, ExitCode};
+
+const HEADER: &str = "\
+# Save and quit to store the workflow, empty the file to cancel.
+# Every {{placeholder}} of the command needs an argument of the same name.
+";
+
+/// `workflow` as YAML to edit, with a few hints on top
+pub(crate) fn to_yaml(workflow: &Workflow) -> WorkflowResult<String> {
+    let yaml = serde_yaml::to_string(workflow).map_err(|e| WorkflowError::Io {
+        path: None,
+        message: format!("unable to write {}: {}", workflow.name, e),
+    })?;
+    Ok(format!("{}{}", HEADER, yaml))
+}
+
+/// Open `path` in `$VISUAL`, `$EDITOR` or else `vi` and wait for it to
+/// exit. The editor is run by `sh`, so it may come with flags like
+/// `code --wait`.
+pub(crate) fn open_editor(path: &Path) -> WorkflowResult<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()
+        .map_err(|e| WorkflowError::Io {
+            path: None,
+            message: format!("unable to run {}: {}", editor, e),
+        })?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(WorkflowError::Io {
+            path: None,
+            message: format!("{} exited with {}, nothing saved", editor, status),
+        }),
+    }
+}
+
+/// Show `error` and ask whether to edit the workflow again
+pub(crate) fn ask_again(error: &WorkflowError) -> WorkflowResult<bool> {
+    eprintln!("{}", error);
+    if !stdin().is_terminal() {
+        return Ok(false);
+    }
+    let mut stderr = stderr();
+    write!(stderr, "Edit again? [Y/n] ")?;
+    stderr.flush()?;
+    let mut line = String::new();
+    stdin().lock().read_line(&mut line)?;
+    Ok(!matches!(line.trim().to_lowercase().as_str(), "n" | "no"))
+}
+
+/// The workflow in `text`, checked like `validate` does, or `None` if
+/// there is nothing but comments left
+fn parse(path: &Path, text: &str) -> WorkflowResult<Option<Workflow>> {
+    let empty = text
+        .lines()
+        .map(str::trim)
+        .all(|line| line.is_empty() || line.starts_with('#') || line == "---");
+    if empty {
+        return Ok(None);
+    }
+    let workflow: Workflow =
+        serde_yaml::from_str(text).map_err(|e| WorkflowError::parse(path, e))?;
+    workflow.check()?;
+    Ok(Some(workflow))
+}
+
+/// Write `text` to a new file in `dir` and `edit` it until it holds a
+/// valid workflow, or `again` gives up after an invalid one. The file is
+/// removed afterwards. Returns `None` if the file was emptied to cancel.
+pub(crate) fn edit_workflow(
+    dir: &Path,
+    text: &str,
+    mut edit: impl FnMut(&Path) -> WorkflowResult<()>,
+    mut again: impl FnMut(&WorkflowError) -> WorkflowResult<bool>,
+) -> WorkflowResult<Option<Workflow>> {
+    // a fresh name every time, so neither a stale file nor a link someone
+    // else put there gets in the way
+    let mut file = tempfile::Builder::new()
+        .prefix("workflowbrowser-")
+        .suffix(".yaml")
+        .tempfile_in(dir)
+        .map_err(|e| WorkflowError::io(dir, e))?;
+    file.write_all(text.as_bytes())
+        .map_err(|e| WorkflowError::io(file.path(), e))?;
+    let path = file.path();
+
+    loop {
+        if let Err(error) = edit(path) {
+            break Err(error);
+        }
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => break Err(WorkflowError::io(path, e)),
+        };
+        match parse(path, &text) {
+            Err(error) => match again(&error) {
+                Ok(true) => continue,
+                Ok(false) => break Err(error),
+                Err(e) => break Err(e),
+            },
+            parsed => break parsed,
+        }
+    }
+}
+
+impl EditCommand {
+    /// Edit the workflow with `edit` and save it over the file it was
+    /// loaded from, or as a copy to the write target if its source is not
+    /// writable
+    fn edit(
+        &self,
+        repo: &mut dyn WorkflowRepository,
+        dir: &Path,
+        edit: impl FnMut(&Path) -> WorkflowResult<()>,
+        again: impl FnMut(&WorkflowError) -> WorkflowResult<bool>,
+    ) -> WorkflowResult<Option<Workflow>> {
+        let original = find_workflow(repo, &self.workflow)?;
+        let Some(mut edited) = edit_workflow(dir, &to_yaml(&original)?, edit, again)? else {
+            return Ok(None);
+        };
+        edited.source = original.source.clone();
+        if edited == original {
+            return Ok(None);
+        }
+        repo.save_workflow(edited.clone())?;
+        Ok(Some(edited))
+    }
+}
+
+impl HandleCommand for EditCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+
+        let mut repo = repo.borrow_mut();
+        match self.edit(&mut *repo, &std::env::temp_dir(), open_editor, ask_again)? {
+            Some(workflow) => eprintln!("saved {}", workflow.name),
+            None => eprintln!("nothing changed"),
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::edit_command::edit_workflow;
+    use crate::command::EditCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::repository::WorkflowRepository;
+    use std::path::Path;
+
+    fn copy_fixtures(dir: &Path) -> DirectoryRepository {
+        for file in ["sample.yaml", "demo2.yaml"] {
+            std::fs::copy(
+                Path::new("tests/fixtures/workflows").join(file),
+                dir.join(file),
+            )
+            .unwrap();
+        }
+        DirectoryRepository::new(dir.to_path_buf())
+    }
+
+    #[test]
+    fn test_edit_again_until_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        // left behind by an earlier run
+        std::fs::write(dir.path().join("edit.yaml"), "stale").unwrap();
+        let mut edits = vec![
+            "name: Greet\ncommand: echo {{name}}\n",
+            "name: Greet\ncommand: [echo\n",
+            "name: Greet\ncommand: echo {{name}}\narguments:\n  - name: name\n",
+        ];
+        let mut errors = vec![];
+        let workflow = edit_workflow(
+            dir.path(),
+            "# start\n",
+            |path| {
+                std::fs::write(path, edits.remove(0)).unwrap();
+                Ok(())
+            },
+            |error| {
+                errors.push(error.to_string());
+                Ok(true)
+            },
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(workflow.arguments[0].name, "name");
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("unknown placeholder(s) {{name}}"));
+        assert!(errors[1].starts_with("unable to parse"));
+        let left: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(left.len(), 1);
+    }
+
+    #[test]
+    fn test_edit_gives_up_or_cancels() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |text: &'static str| {
+            move |path: &Path| {
+                std::fs::write(path, text).unwrap();
+                Ok(())
+            }
+        };
+        let result = edit_workflow(dir.path(), "", write("name: x\n"), |_| Ok(false));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("missing field `command`"));
+        let result = edit_workflow(dir.path(), "name: x\n", write("# nothing\n\n"), |_| {
+            Ok(true)
+        });
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_edit_saves_over_the_same_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = copy_fixtures(dir.path());
+        let command = EditCommand {
+            workflow: "local:demo2.yaml".to_string(),
+        };
+        let edits = tempfile::tempdir().unwrap();
+        let edited = command
+            .edit(
+                &mut repo,
+                edits.path(),
+                |path| {
+                    let text = std::fs::read_to_string(path).unwrap();
+                    std::fs::write(path, text.replace("ls -al", "ls -l")).unwrap();
+                    Ok(())
+                },
+                |_| Ok(false),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert!(edited.command.contains("ls -l {{pattern}}"));
+        assert_eq!(repo.get_workflows().unwrap().len(), 2);
+        let saved = std::fs::read_to_string(dir.path().join("demo2.yaml")).unwrap();
+        assert!(saved.contains("ls -l {{pattern}}"));
+        assert!(!saved.contains("# Save and quit"));
+
+        // leaving the file as it is saves nothing
+        let unchanged = command.edit(&mut repo, edits.path(), |_| Ok(()), |_| Ok(false));
+        assert!(unchanged.unwrap().is_none());
+    }
+}
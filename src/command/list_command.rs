@@ -0,0 +1,80 @@
+use crate::command::output;
+use crate::command::{HandleCommand, ListCommand};
+use crate::prelude::WorkflowResult;
+use crate::workflow::file_format::Workflow;
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+impl ListCommand {
+    /// Whether `workflow` passes the source and shell filters. Like the
+    /// `shell:` search term, workflows without shells are for any shell.
+    fn accepts(&self, workflow: &Workflow) -> bool {
+        let source = self
+            .source
+            .as_ref()
+            .is_none_or(|source| workflow.id().is_some_and(|id| &id.repository == source));
+        let shell = self
+            .shell
+            .as_ref()
+            .is_none_or(|shell| workflow.shells.is_empty() || workflow.shells.contains(shell));
+        source && shell
+    }
+
+    fn write(&self, repo: &dyn WorkflowRepository, out: &mut impl Write) -> WorkflowResult<()> {
+        let workflows: Vec<Workflow> = repo
+            .get_workflows()?
+            .into_iter()
+            .filter(|workflow| self.accepts(workflow))
+            .collect();
+        output::write(&workflows, self.format, &self.fields, out)
+    }
+}
+
+impl HandleCommand for ListCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+        let repo = repo.borrow();
+        self.write(&*repo, &mut stdout().lock())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::output::{Format, OutputField};
+    use crate::command::ListCommand;
+    use crate::prelude::composite::CompositeRepository;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::file_format::Shell;
+    use std::path::PathBuf;
+
+    fn list(source: Option<&str>, shell: Option<Shell>) -> String {
+        let fixtures = || DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let repo = CompositeRepository::new(vec![
+            Box::new(fixtures()),
+            Box::new(fixtures().with_id("other")),
+        ]);
+        let command = ListCommand {
+            source: source.map(str::to_string),
+            shell,
+            format: Format::Tsv,
+            fields: vec![OutputField::Id],
+        };
+        let mut out = vec![];
+        command.write(&repo, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_list_filters_by_source_and_shell() {
+        assert_eq!(list(None, None).lines().count(), 6);
+        let other = list(Some("other"), None);
+        assert_eq!(other.lines().count(), 3);
+        assert!(other.lines().all(|id| id.starts_with("other:")));
+        // none of the fixtures is limited to a shell
+        assert_eq!(list(Some("local"), Some(Shell::Fish)).lines().count(), 3);
+        assert!(list(Some("nope"), None).is_empty());
+    }
+}
@@ -1,27 +1,253 @@
+mod delete_command;
+mod edit_command;
+mod list_command;
+mod new_command;
+pub mod output;
+mod refresh_command;
+mod run_command;
 mod search_command;
+mod shell_init_command;
+mod show_command;
+mod tags_command;
+mod validate_command;
 
+use crate::command::output::{Format, OutputField};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::{Argument, Shell, Workflow};
+use crate::workflow::repository::WorkflowRepository;
+use crate::workflow::source::WorkflowId;
 use clap::{Args, Parser, Subcommand};
 use di::ServiceProvider;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Write the selected command to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// The shell the selected command is inserted into, to quote values
+    /// for. Defaults to the only shell of the workflow or else $SHELL
+    #[arg(long, value_enum)]
+    pub shell: Option<Shell>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Search workflows by query
     Search(SearchCommand),
+    /// Report workflow files that can not be loaded
+    Validate(ValidateCommand),
+    /// Print a snippet binding Ctrl-G to pick a workflow into the command line
+    ShellInit(ShellInitCommand),
+    /// Fill in the arguments of a workflow and run it
+    Run(RunCommand),
+    /// List all workflows
+    List(ListCommand),
+    /// Show everything about a single workflow
+    Show(ShowCommand),
+    /// Count how many workflows have each tag
+    Tags(TagsCommand),
+    /// Write a new workflow in $EDITOR and save it to the write target
+    New(NewCommand),
+    /// Change a workflow in $EDITOR
+    Edit(EditCommand),
+    /// Delete a workflow and its file
+    Delete(DeleteCommand),
+    /// Pull the git sources and reload all workflows, listing what changed
+    Refresh(RefreshCommand),
 }
 
 #[derive(Debug, Args)]
 pub struct SearchCommand {
+    /// Words to fuzzily search for, narrowed down with `tag:`, `shell:`,
+    /// `author:` and `arg:`, `"quoted phrases"`, `-` to negate a term and
+    /// `OR` between alternatives
     #[arg(short, long)]
     pub query: String,
+    /// How to write the matches
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: Format,
+    /// Write at most this many of the best matches
+    #[arg(short, long)]
+    pub limit: Option<usize>,
+    /// Comma separated fields to write, all but tsv write all by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fields: Vec<OutputField>,
+}
+
+#[derive(Debug, Args)]
+pub struct ValidateCommand {}
+
+#[derive(Debug, Args)]
+pub struct RunCommand {
+    /// Id (`source:path`) or name of the workflow
+    pub workflow: String,
+    /// Value of an argument, arguments without a value or default value
+    /// are asked for
+    #[arg(short, long = "arg", value_name = "NAME=VALUE", value_parser = run_command::parse_argument)]
+    pub args: Vec<(String, String)>,
+}
+
+#[derive(Debug, Args)]
+pub struct ListCommand {
+    /// Only workflows of the source with this id
+    #[arg(long)]
+    pub source: Option<String>,
+    /// Only workflows for this shell, or for any shell
+    #[arg(long, value_enum)]
+    pub shell: Option<Shell>,
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: Format,
+    /// Comma separated fields to write, all but tsv write all by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fields: Vec<OutputField>,
+}
+
+#[derive(Debug, Args)]
+pub struct ShowCommand {
+    /// Id (`source:path`) or name of the workflow
+    pub workflow: String,
+    /// Value of an argument to render the command with, arguments without
+    /// one use their default value
+    #[arg(short, long = "arg", value_name = "NAME=VALUE", value_parser = run_command::parse_argument)]
+    pub args: Vec<(String, String)>,
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: Format,
+    /// Comma separated fields to write, all but tsv write all by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fields: Vec<OutputField>,
+}
+
+#[derive(Debug, Args)]
+pub struct TagsCommand {
+    #[arg(short, long, value_enum, default_value = "text")]
+    pub format: Format,
+}
+
+#[derive(Debug, Args)]
+pub struct NewCommand {
+    /// Name to start the workflow with
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Command to start the workflow with, its placeholders become
+    /// arguments
+    #[arg(short, long)]
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct EditCommand {
+    /// Id (`source:path`) or name of the workflow, workflows of git
+    /// sources are saved as a copy to the write target
+    pub workflow: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteCommand {
+    /// Id (`source:path`) or name of the workflow, workflows of git sources
+    /// can not be deleted
+    pub workflow: String,
+}
+
+#[derive(Debug, Args)]
+pub struct RefreshCommand {}
+
+#[derive(Debug, Args)]
+pub struct ShellInitCommand {
+    /// The shell to print the snippet for, source it in its rc file
+    #[arg(value_enum)]
+    pub shell: Shell,
 }
 
 pub trait HandleCommand {
-    fn handle(&self, repo: &ServiceProvider);
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode>;
+}
+
+impl HandleCommand for Commands {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        match self {
+            Commands::Search(command) => command.handle(services),
+            Commands::Validate(command) => command.handle(services),
+            Commands::ShellInit(command) => command.handle(services),
+            Commands::Run(command) => command.handle(services),
+            Commands::List(command) => command.handle(services),
+            Commands::Show(command) => command.handle(services),
+            Commands::Tags(command) => command.handle(services),
+            Commands::New(command) => command.handle(services),
+            Commands::Edit(command) => command.handle(services),
+            Commands::Delete(command) => command.handle(services),
+            Commands::Refresh(command) => command.handle(services),
+        }
+    }
+}
+
+/// Find a workflow by its id or else by its name, which has to be unique
+/// across all repositories
+fn find_workflow(repo: &dyn WorkflowRepository, id_or_name: &str) -> WorkflowResult<Workflow> {
+    if let Ok(id) = id_or_name.parse::<WorkflowId>() {
+        if let Ok(workflow) = repo.get_workflow(&id) {
+            return Ok(workflow);
+        }
+    }
+    let mut found: Vec<Workflow> = repo
+        .get_workflows()?
+        .into_iter()
+        .filter(|w| w.name.eq_ignore_ascii_case(id_or_name))
+        .collect();
+    match found.len() {
+        0 => Err(WorkflowError::NotFound(format!("workflow {}", id_or_name))),
+        1 => Ok(found.remove(0)),
+        _ => {
+            let ids: Vec<String> = found
+                .iter()
+                .filter_map(|w| w.id().map(WorkflowId::to_string))
+                .collect();
+            Err(WorkflowError::Conflict(format!(
+                "{} workflows are named {}, use one of {}",
+                found.len(),
+                id_or_name,
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+/// The value of every argument of `workflow`: the last one given in `args`,
+/// the default value or else what `missing` returns, which gives up with
+/// `None`. Values are validated and `args` the workflow does not have are
+/// an error.
+fn argument_values(
+    workflow: &Workflow,
+    args: &[(String, String)],
+    mut missing: impl FnMut(&Argument) -> WorkflowResult<Option<String>>,
+) -> WorkflowResult<Option<HashMap<String, String>>> {
+    if let Some((name, _)) = args
+        .iter()
+        .find(|(name, _)| !workflow.arguments.iter().any(|a| &a.name == name))
+    {
+        return Err(WorkflowError::InvalidArgument(format!(
+            "{} has no argument {}",
+            workflow.name, name
+        )));
+    }
+    let mut values = HashMap::new();
+    for argument in &workflow.arguments {
+        let given = args.iter().rev().find(|(name, _)| name == &argument.name);
+        let value = match (given, &argument.default_value) {
+            (Some((_, value)), _) => value.clone(),
+            (None, Some(default_value)) => default_value.clone(),
+            (None, None) => match missing(argument)? {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+        argument.validate(&value)?;
+        values.insert(argument.name.clone(), value);
+    }
+    Ok(Some(values))
 }
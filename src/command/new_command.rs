@@ -0,0 +1,99 @@
+use crate::command::edit_command::{ask_again, edit_workflow, open_editor, to_yaml};
+use crate::command::{HandleCommand, NewCommand};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::{Argument, Workflow};
+use crate::workflow::repository::WorkflowRepository;
+use crate::workflow::template;
+use di::ServiceProvider;
+use std::path::Path;
+use std::process::ExitCode;
+
+impl NewCommand {
+    /// A workflow to start from, with an argument for every placeholder
+    /// of the command
+    fn scaffold(&self) -> Workflow {
+        let name = self.name.as_deref().unwrap_or("New workflow");
+        let command = self.command.as_deref().unwrap_or("echo {{message}}");
+        let arguments = template::placeholders(&template::tokenize(command))
+            .into_iter()
+            .map(Argument::new)
+            .collect();
+        Workflow::new(name, command).with_arguments(arguments)
+    }
+
+    fn create(
+        &self,
+        repo: &mut dyn WorkflowRepository,
+        dir: &Path,
+        edit: impl FnMut(&Path) -> WorkflowResult<()>,
+        again: impl FnMut(&WorkflowError) -> WorkflowResult<bool>,
+    ) -> WorkflowResult<Option<Workflow>> {
+        let Some(workflow) = edit_workflow(dir, &to_yaml(&self.scaffold())?, edit, again)? else {
+            return Ok(None);
+        };
+        repo.save_workflow(workflow.clone())?;
+        Ok(Some(workflow))
+    }
+}
+
+impl HandleCommand for NewCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+
+        let mut repo = repo.borrow_mut();
+        match self.create(&mut *repo, &std::env::temp_dir(), open_editor, ask_again)? {
+            Some(workflow) => eprintln!("saved {}", workflow.name),
+            None => eprintln!("cancelled"),
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::NewCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::repository::WorkflowRepository;
+
+    #[test]
+    fn test_scaffold_has_the_placeholders_as_arguments() {
+        let command = NewCommand {
+            name: Some("Undo".to_string()),
+            command: Some("git reset {{mode}} HEAD~{{count}} {{mode}}".to_string()),
+        };
+        let workflow = command.scaffold();
+        let names: Vec<&str> = workflow.arguments.iter().map(|a| a.name()).collect();
+        assert_eq!(names, ["mode", "count"]);
+        assert!(workflow.check().is_ok());
+    }
+
+    #[test]
+    fn test_new_workflow_is_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("workflows")).unwrap();
+        let mut repo = DirectoryRepository::new(dir.path().join("workflows"));
+        let command = NewCommand {
+            name: Some("Say hello".to_string()),
+            command: None,
+        };
+        let mut seen = String::new();
+        let saved = command
+            .create(
+                &mut repo,
+                dir.path(),
+                |path| {
+                    seen = std::fs::read_to_string(path).unwrap();
+                    Ok(())
+                },
+                |_| Ok(false),
+            )
+            .unwrap()
+            .unwrap();
+
+        assert!(seen.starts_with("# Save and quit"));
+        assert!(seen.contains("name: Say hello\ncommand: echo {{message}}\n"));
+        assert_eq!(saved.name, "Say hello");
+        let id = "local:say-hello.yaml".parse().unwrap();
+        assert_eq!(repo.get_workflow(&id).unwrap().arguments[0].name, "message");
+    }
+}
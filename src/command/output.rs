@@ -0,0 +1,375 @@
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::{Argument, ArgumentType, Workflow};
+use clap::ValueEnum;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use std::io::Write;
+
+/// How workflows are written by commands that print them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// for humans, the fields of a workflow in a block
+    Text,
+    /// a single JSON array
+    Json,
+    /// one JSON object per line
+    Jsonl,
+    /// a YAML list
+    Yaml,
+    /// one line per workflow, fields separated by tabs
+    Tsv,
+}
+
+/// A field of the written workflows
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputField {
+    Id,
+    Name,
+    Description,
+    Command,
+    Tags,
+    Arguments,
+    Source,
+}
+
+impl OutputField {
+    /// The fields written when none are selected
+    pub fn defaults(format: Format) -> Vec<OutputField> {
+        match format {
+            Format::Tsv => vec![OutputField::Id, OutputField::Name, OutputField::Command],
+            _ => OutputField::value_variants().to_vec(),
+        }
+    }
+
+    /// The name of the field, as given to `--fields`
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize)]
+struct ArgumentRecord<'a> {
+    name: &'a str,
+    description: Option<&'a str>,
+    default_value: Option<&'a str>,
+    #[serde(rename = "type")]
+    kind: ArgumentType,
+    options: &'a [String],
+}
+
+impl<'a> From<&'a Argument> for ArgumentRecord<'a> {
+    fn from(argument: &'a Argument) -> Self {
+        ArgumentRecord {
+            name: &argument.name,
+            description: argument.description.as_deref(),
+            default_value: argument.default_value.as_deref(),
+            kind: argument.kind,
+            options: &argument.options,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SourceRecord<'a> {
+    repository: &'a str,
+    file: String,
+    commit: Option<&'a str>,
+}
+
+/// A workflow as written by the JSON and YAML formats, with the fields in
+/// the order they were asked for. The names and types of the fields are
+/// relied upon by scripts, only add to them.
+struct Record<'a> {
+    workflow: &'a Workflow,
+    fields: &'a [OutputField],
+}
+
+impl Serialize for Record<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let workflow = self.workflow;
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for field in self.fields {
+            let name = field.name();
+            match field {
+                OutputField::Id => {
+                    map.serialize_entry(&name, &workflow.id().map(|id| id.to_string()))?
+                }
+                OutputField::Name => map.serialize_entry(&name, &workflow.name)?,
+                OutputField::Description => map.serialize_entry(&name, &workflow.description)?,
+                OutputField::Command => map.serialize_entry(&name, &workflow.command)?,
+                OutputField::Tags => map.serialize_entry(&name, &workflow.tags)?,
+                OutputField::Arguments => {
+                    let arguments: Vec<ArgumentRecord> =
+                        workflow.arguments.iter().map(Into::into).collect();
+                    map.serialize_entry(&name, &arguments)?
+                }
+                OutputField::Source => {
+                    let source = workflow.source.as_ref().map(|source| SourceRecord {
+                        repository: &source.id.repository,
+                        file: source.file.display().to_string(),
+                        commit: source.commit.as_deref(),
+                    });
+                    map.serialize_entry(&name, &source)?
+                }
+            }
+        }
+        map.end()
+    }
+}
+
+/// The value of `field` on a single line
+fn plain(workflow: &Workflow, field: OutputField) -> String {
+    match field {
+        OutputField::Id => workflow.id().map(|id| id.to_string()).unwrap_or_default(),
+        OutputField::Name => workflow.name.clone(),
+        OutputField::Description => workflow.description.clone().unwrap_or_default(),
+        OutputField::Command => workflow.command.trim_end().to_string(),
+        OutputField::Tags => workflow.tags.join(","),
+        OutputField::Arguments => {
+            let names: Vec<&str> = workflow.arguments.iter().map(|a| a.name.as_str()).collect();
+            names.join(",")
+        }
+        OutputField::Source => workflow
+            .source
+            .as_ref()
+            .map(|source| source.file.display().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Escape what would break a line of tab separated values
+fn escape_tsv(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn serialization_error(error: impl std::fmt::Display) -> WorkflowError {
+    WorkflowError::Io {
+        path: None,
+        message: format!("unable to write workflows: {}", error),
+    }
+}
+
+/// Write `workflows` to `out` in `format`, with only `fields`, or the
+/// default fields of `format` if there are none
+pub fn write(
+    workflows: &[Workflow],
+    format: Format,
+    fields: &[OutputField],
+    out: &mut impl Write,
+) -> WorkflowResult<()> {
+    let fields = match fields.is_empty() {
+        true => OutputField::defaults(format),
+        // in the order asked for, each field once
+        false => fields.iter().fold(vec![], |mut unique, field| {
+            if !unique.contains(field) {
+                unique.push(*field);
+            }
+            unique
+        }),
+    };
+    let records = || {
+        workflows.iter().map(|workflow| Record {
+            workflow,
+            fields: &fields,
+        })
+    };
+    match format {
+        Format::Text if fields == OutputField::defaults(format) => {
+            for (i, workflow) in workflows.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                write!(out, "{}", workflow)?;
+            }
+        }
+        Format::Text => {
+            for (i, workflow) in workflows.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out)?;
+                }
+                for field in &fields {
+                    writeln!(out, "{}: {}", field.name(), plain(workflow, *field))?;
+                }
+            }
+        }
+        Format::Json => {
+            let records: Vec<Record> = records().collect();
+            serde_json::to_writer_pretty(&mut *out, &records).map_err(serialization_error)?;
+            writeln!(out)?;
+        }
+        Format::Jsonl => {
+            for record in records() {
+                serde_json::to_writer(&mut *out, &record).map_err(serialization_error)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Yaml => {
+            let records: Vec<Record> = records().collect();
+            serde_yaml::to_writer(&mut *out, &records).map_err(serialization_error)?;
+        }
+        Format::Tsv => {
+            for workflow in workflows {
+                let values: Vec<String> = fields
+                    .iter()
+                    .map(|field| escape_tsv(&plain(workflow, *field)))
+                    .collect();
+                writeln!(out, "{}", values.join("\t"))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct TagRecord<'a> {
+    tag: &'a str,
+    count: usize,
+}
+
+/// Write tags with the number of workflows having them to `out`
+pub fn write_tags(
+    tags: &[(&str, usize)],
+    format: Format,
+    out: &mut impl Write,
+) -> WorkflowResult<()> {
+    let records = || {
+        tags.iter()
+            .map(|(tag, count)| TagRecord { tag, count: *count })
+    };
+    match format {
+        Format::Text => {
+            let width = tags.iter().map(|(_, count)| count.to_string().len()).max();
+            for (tag, count) in tags {
+                writeln!(
+                    out,
+                    "{:>width$}  {}",
+                    count,
+                    tag,
+                    width = width.unwrap_or(0)
+                )?;
+            }
+        }
+        Format::Json => {
+            let records: Vec<TagRecord> = records().collect();
+            serde_json::to_writer_pretty(&mut *out, &records).map_err(serialization_error)?;
+            writeln!(out)?;
+        }
+        Format::Jsonl => {
+            for record in records() {
+                serde_json::to_writer(&mut *out, &record).map_err(serialization_error)?;
+                writeln!(out)?;
+            }
+        }
+        Format::Yaml => {
+            let records: Vec<TagRecord> = records().collect();
+            serde_yaml::to_writer(&mut *out, &records).map_err(serialization_error)?;
+        }
+        Format::Tsv => {
+            for (tag, count) in tags {
+                writeln!(out, "{}\t{}", escape_tsv(tag), count)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::output::{write, write_tags, Format, OutputField};
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::file_format::{Argument, Workflow};
+    use crate::workflow::repository::WorkflowRepository;
+    use std::path::PathBuf;
+
+    fn output(workflows: &[Workflow], format: Format, fields: &[OutputField]) -> String {
+        let mut out = vec![];
+        write(workflows, format, fields, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn sample() -> Vec<Workflow> {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let id = "local:sample.yaml".parse().unwrap();
+        vec![repo.get_workflow(&id).unwrap()]
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let out = output(&sample(), Format::Json, &[]);
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let workflow = &json[0];
+        assert_eq!(workflow["id"], "local:sample.yaml");
+        assert_eq!(workflow["tags"], serde_json::json!(["curl"]));
+        assert_eq!(workflow["arguments"][0]["name"], "header");
+        assert_eq!(workflow["arguments"][0]["type"], "string");
+        assert_eq!(
+            workflow["arguments"][0]["default_value"],
+            serde_json::Value::Null
+        );
+        assert_eq!(workflow["source"]["repository"], "local");
+        assert!(workflow["source"]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with("sample.yaml"));
+        assert_eq!(workflow["source"]["commit"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_selected_fields_only() {
+        let fields = [OutputField::Name, OutputField::Id, OutputField::Name];
+        assert_eq!(
+            output(&sample(), Format::Jsonl, &fields),
+            "{\"name\":\"Attach a header to an HTTP request with cURL\",\"id\":\"local:sample.yaml\"}\n"
+        );
+        assert_eq!(
+            output(&sample(), Format::Yaml, &fields),
+            "- name: Attach a header to an HTTP request with cURL\n  id: local:sample.yaml\n"
+        );
+        assert_eq!(
+            output(
+                &sample(),
+                Format::Tsv,
+                &[OutputField::Command, OutputField::Id]
+            ),
+            "curl --header \"{{header}}\" {{url}}\\ndemo\tlocal:sample.yaml\n"
+        );
+        assert_eq!(
+            output(&sample(), Format::Text, &[OutputField::Tags]),
+            "tags: curl\n"
+        );
+    }
+
+    #[test]
+    fn test_tsv_escapes_tabs_and_newlines() {
+        let workflow = Workflow::new("Print\ttab", "printf 'a\\tb'\necho done\n")
+            .with_arguments(vec![Argument::new("a"), Argument::new("b")]);
+        assert_eq!(
+            output(std::slice::from_ref(&workflow), Format::Tsv, &[]),
+            "\tPrint\\ttab\tprintf 'a\\\\tb'\\necho done\n"
+        );
+        assert_eq!(
+            output(&[workflow], Format::Tsv, &[OutputField::Arguments]),
+            "a,b\n"
+        );
+    }
+
+    #[test]
+    fn test_write_tags() {
+        let tags = [("git", 12), ("docker", 3)];
+        let mut out = vec![];
+        write_tags(&tags, Format::Text, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "12  git\n 3  docker\n");
+        let mut out = vec![];
+        write_tags(&tags, Format::Jsonl, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"tag\":\"git\",\"count\":12}\n{\"tag\":\"docker\",\"count\":3}\n"
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::command::{HandleCommand, RefreshCommand};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+use di::ServiceProvider;
+use std::io::{stderr, stdout, Write};
+use std::process::ExitCode;
+
+impl RefreshCommand {
+    /// One line per changed workflow, e.g. `added team:git/undo.yaml`
+    fn write(changes: &ChangeSet, out: &mut impl Write) -> WorkflowResult<()> {
+        let kinds = [
+            ("added", &changes.added),
+            ("modified", &changes.modified),
+            ("removed", &changes.removed),
+        ];
+        for (kind, ids) in kinds {
+            for id in ids {
+                writeln!(out, "{} {}", kind, id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the changes of a refresh to `out`, then why sources failed to
+    /// `err`. Fails if any source did.
+    fn report(
+        refreshed: WorkflowResult<ChangeSet>,
+        out: &mut impl Write,
+        err: &mut impl Write,
+    ) -> WorkflowResult<ExitCode> {
+        let (changes, errors) = match refreshed {
+            Ok(changes) => (changes, vec![]),
+            Err(WorkflowError::Refresh { changes, errors }) => (changes, errors),
+            Err(error) => return Err(error),
+        };
+        Self::write(&changes, out)?;
+        for error in &errors {
+            writeln!(err, "{}: {}", error.source, error.error)?;
+        }
+        if !errors.is_empty() {
+            return Ok(ExitCode::FAILURE);
+        }
+        if changes.is_empty() {
+            writeln!(err, "everything is up to date")?;
+        }
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+impl HandleCommand for RefreshCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+
+        let refreshed = repo.borrow_mut().refresh();
+        Self::report(refreshed, &mut stdout().lock(), &mut stderr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::RefreshCommand;
+    use crate::prelude::composite::CompositeRepository;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+    use crate::workflow::source::WorkflowId;
+    use std::process::ExitCode;
+
+    #[test]
+    fn test_write_lists_every_change() {
+        let changes = ChangeSet {
+            added: vec![WorkflowId::new("team", "c.yaml")],
+            modified: vec![WorkflowId::new("team", "a.yaml")],
+            removed: vec![WorkflowId::new("local", "b.yaml")],
+        };
+        let mut out = vec![];
+        RefreshCommand::write(&changes, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "added team:c.yaml\nmodified team:a.yaml\nremoved local:b.yaml\n"
+        );
+    }
+
+    #[test]
+    fn test_report_changes_and_failed_sources() {
+        let mine = tempfile::tempdir().unwrap();
+        let gone = tempfile::tempdir().unwrap();
+        let mut repo = CompositeRepository::new(vec![
+            Box::new(DirectoryRepository::new(mine.path().to_path_buf()).with_id("mine")),
+            Box::new(DirectoryRepository::new(gone.path().to_path_buf()).with_id("gone")),
+        ]);
+        std::fs::write(mine.path().join("new.yaml"), "name: New\ncommand: ls\n").unwrap();
+        let gone_path = gone.path().to_path_buf();
+        gone.close().unwrap();
+
+        let (mut out, mut err) = (vec![], vec![]);
+        let code = RefreshCommand::report(repo.refresh(), &mut out, &mut err).unwrap();
+        assert_eq!(code, ExitCode::FAILURE);
+        assert_eq!(String::from_utf8(out).unwrap(), "added mine:new.yaml\n");
+        let err = String::from_utf8(err).unwrap();
+        assert!(
+            err.starts_with(&format!("gone: {}", gone_path.display())),
+            "{}",
+            err
+        );
+        assert_eq!(repo.get_workflows().unwrap().len(), 1);
+    }
+}
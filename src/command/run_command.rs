@@ -0,0 +1,215 @@
+use crate::command::{argument_values, find_workflow, HandleCommand, RunCommand};
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::{Argument, ArgumentType, Shell, Workflow};
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::collections::HashMap;
+use std::io::{stderr, stdin, BufRead, IsTerminal, Write};
+use std::process::{Command, ExitCode};
+
+/// Parse a `name=value` pair given with `--arg`
+pub(crate) fn parse_argument(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got `{}`", value)),
+    }
+}
+
+/// Ask for the value of `argument` on the terminal
+fn prompt(argument: &Argument) -> WorkflowResult<String> {
+    if !stdin().is_terminal() {
+        return Err(WorkflowError::InvalidArgument(format!(
+            "missing value for argument {}, pass it with --arg {}=VALUE",
+            argument.name, argument.name
+        )));
+    }
+    let mut stderr = stderr();
+    write!(stderr, "{}", argument.name)?;
+    if let Some(description) = &argument.description {
+        write!(stderr, " ({})", description)?;
+    }
+    match argument.kind {
+        ArgumentType::Bool => write!(stderr, " [true|false]: ")?,
+        ArgumentType::Enum => write!(stderr, " [{}]: ", argument.options.join("|"))?,
+        _ => write!(stderr, ": ")?,
+    }
+    stderr.flush()?;
+    let mut line = String::new();
+    stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// The shell to run `workflow` in: the user's shell if the workflow runs
+/// in it, the first shell of the workflow otherwise
+fn shell_program(workflow: &Workflow, user_shell: Option<&str>) -> String {
+    let user_shell = user_shell.unwrap_or("sh");
+    let kind = Shell::from_program(user_shell);
+    match workflow.shells.first() {
+        Some(first) if !kind.is_some_and(|kind| workflow.shells.contains(&kind)) => {
+            first.to_string()
+        }
+        _ => user_shell.to_string(),
+    }
+}
+
+impl RunCommand {
+    fn resolve(&self, repo: &dyn WorkflowRepository) -> WorkflowResult<Workflow> {
+        find_workflow(repo, &self.workflow)
+    }
+
+    /// The value of every argument of `workflow`: given with `--arg`, the
+    /// default value or else asked for with `prompt`
+    fn values(
+        &self,
+        workflow: &Workflow,
+        mut prompt: impl FnMut(&Argument) -> WorkflowResult<String>,
+    ) -> WorkflowResult<HashMap<String, String>> {
+        // every missing value is asked for, nothing gives up
+        let values = argument_values(workflow, &self.args, |argument| prompt(argument).map(Some))?;
+        Ok(values.unwrap_or_default())
+    }
+
+    /// Run `command` with `shell -c`, inheriting stdin, stdout and stderr,
+    /// and return its exit code
+    fn execute(shell: &str, command: &str) -> WorkflowResult<u8> {
+        let status = Command::new(shell)
+            .arg("-c")
+            .arg(command)
+            .status()
+            .map_err(|e| WorkflowError::Io {
+                path: None,
+                message: format!("unable to run {}: {}", shell, e),
+            })?;
+        // there is no exit code if the command was killed by a signal
+        Ok(status.code().map_or(1, |code| code as u8))
+    }
+
+    pub fn run(&self, repo: &dyn WorkflowRepository) -> WorkflowResult<ExitCode> {
+        let workflow = self.resolve(repo)?;
+        let values = self.values(&workflow, prompt)?;
+        let values = values
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let shell = shell_program(&workflow, std::env::var("SHELL").ok().as_deref());
+        let kind = Shell::from_program(&shell).unwrap_or(Shell::Bash);
+        let command = workflow.render_quoted(values, &kind)?;
+        Ok(ExitCode::from(Self::execute(&shell, &command)?))
+    }
+}
+
+impl HandleCommand for RunCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+        let repo = repo.borrow();
+        self.run(&*repo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::run_command::{parse_argument, shell_program};
+    use crate::command::RunCommand;
+    use crate::prelude::composite::CompositeRepository;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::prelude::WorkflowError;
+    use crate::workflow::file_format::{Argument, ArgumentType, Shell, Workflow};
+    use std::path::PathBuf;
+
+    fn run(workflow: &str, args: &[(&str, &str)]) -> RunCommand {
+        RunCommand {
+            workflow: workflow.to_string(),
+            args: args
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_argument() {
+        assert_eq!(
+            parse_argument("url=https://a.b/?c=d"),
+            Ok(("url".to_string(), "https://a.b/?c=d".to_string()))
+        );
+        assert!(parse_argument("url").is_err());
+        assert!(parse_argument("=value").is_err());
+    }
+
+    #[test]
+    fn test_resolve_by_id_or_unique_name() {
+        let fixtures = || DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let repo = CompositeRepository::new(vec![
+            Box::new(fixtures()),
+            Box::new(fixtures().with_id("other")),
+        ]);
+
+        let found = run("other:sample.yaml", &[]).resolve(&repo).unwrap();
+        assert_eq!(found.tags, vec!["curl"]);
+        // both repositories have it
+        let found = run("attach a header to an HTTP request with cURL", &[]).resolve(&repo);
+        assert!(matches!(found, Err(WorkflowError::Conflict(_))));
+        let found = run("no such workflow", &[]).resolve(&repo);
+        assert!(matches!(found, Err(WorkflowError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_values_fall_back_to_defaults_and_prompt() {
+        let mut count = Argument::new("count");
+        count.default_value = Some("1".to_string());
+        let workflow = Workflow::new("Undo", "git reset {{mode}} HEAD~{{count}}")
+            .with_arguments(vec![count, Argument::new("mode"), Argument::new("path")]);
+
+        let mut asked = vec![];
+        let values = run("Undo", &[("mode", "--hard")])
+            .values(&workflow, |argument| {
+                asked.push(argument.name.clone());
+                Ok("src".to_string())
+            })
+            .unwrap();
+        assert_eq!(values["count"], "1");
+        assert_eq!(values["mode"], "--hard");
+        assert_eq!(values["path"], "src");
+        assert_eq!(asked, vec!["path"]);
+
+        let values = run("Undo", &[("colour", "red")]).values(&workflow, |_| unreachable!());
+        assert!(matches!(values, Err(WorkflowError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_values_are_validated() {
+        let mut count = Argument::new("count").with_type(ArgumentType::Int);
+        count.min = Some(1);
+        let workflow =
+            Workflow::new("Undo", "git reset HEAD~{{count}}").with_arguments(vec![count]);
+
+        let values = run("Undo", &[("count", "2")]).values(&workflow, |_| unreachable!());
+        assert_eq!(values.unwrap()["count"], "2");
+        let values = run("Undo", &[("count", "0")]).values(&workflow, |_| unreachable!());
+        assert_eq!(
+            values.unwrap_err().to_string(),
+            "invalid argument: count must be at least 1"
+        );
+    }
+
+    #[test]
+    fn test_shell_program_honours_workflow_shells() {
+        let mut workflow = Workflow::new("a", "b");
+        assert_eq!(shell_program(&workflow, Some("/bin/zsh")), "/bin/zsh");
+        assert_eq!(shell_program(&workflow, None), "sh");
+
+        workflow.shells = vec![Shell::Fish, Shell::Zsh];
+        assert_eq!(
+            shell_program(&workflow, Some("/usr/bin/zsh")),
+            "/usr/bin/zsh"
+        );
+        assert_eq!(shell_program(&workflow, Some("/bin/bash")), "fish");
+    }
+
+    #[test]
+    fn test_execute_returns_the_exit_code() {
+        assert_eq!(RunCommand::execute("sh", "true").unwrap(), 0);
+        assert_eq!(RunCommand::execute("sh", "exit 3").unwrap(), 3);
+        assert!(RunCommand::execute("no-such-shell", "true").is_err());
+    }
+}
@@ -1,22 +1,59 @@
+use crate::command::output;
 use crate::command::{HandleCommand, SearchCommand};
-use crate::prelude::git::GitRepository;
+use crate::prelude::WorkflowResult;
+use crate::workflow::file_format::Workflow;
 use crate::workflow::repository::WorkflowRepository;
 use di::ServiceProvider;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+impl SearchCommand {
+    /// Write the best matches of the query to `out`
+    fn write(&self, repo: &dyn WorkflowRepository, out: &mut impl Write) -> WorkflowResult<()> {
+        let found: Vec<Workflow> = repo
+            .query_workflows(&self.query)?
+            .into_iter()
+            .take(self.limit.unwrap_or(usize::MAX))
+            .map(|m| m.workflow)
+            .collect();
+        output::write(&found, self.format, &self.fields, out)
+    }
+}
 
 impl HandleCommand for SearchCommand {
-    fn handle(&self, services: &ServiceProvider) {
-        let repo = services.get::<GitRepository>().unwrap();
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+        let repo = repo.borrow();
+        self.write(&*repo, &mut stdout().lock())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::output::{Format, OutputField};
+    use crate::command::SearchCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_best_matches() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let search = |limit| SearchCommand {
+            query: "demo".to_string(),
+            format: Format::Tsv,
+            limit,
+            fields: vec![OutputField::Id],
+        };
+
+        let mut out = vec![];
+        search(None).write(&repo, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 3);
 
-        println!("Search Command queries for {}", self.query.clone());
-        let query_result = repo.query_workflows(self.query.as_str());
-        if let Ok(workflows) = query_result {
-            for a in workflows {
-                println!(
-                    "---\n{}\n{:?}",
-                    a.command,
-                    a.description.unwrap_or("".to_string())
-                );
-            }
-        }
+        let mut out = vec![];
+        search(Some(1)).write(&repo, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.starts_with("local:"));
     }
 }
@@ -0,0 +1,108 @@
+use crate::command::{HandleCommand, ShellInitCommand};
+use crate::prelude::WorkflowResult;
+use crate::workflow::file_format::Shell;
+use di::ServiceProvider;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+// Every snippet runs the TUI, which draws on stderr, and inserts what it
+// prints on stdout at the cursor of the command line.
+
+const BASH: &str = r#"# workflowbrowser: press Ctrl-G to pick a workflow and insert it at the cursor
+__workflowbrowser_widget() {
+  local selected
+  selected="$(workflowbrowser --shell bash)" || return
+  READLINE_LINE="${READLINE_LINE:0:$READLINE_POINT}${selected}${READLINE_LINE:$READLINE_POINT}"
+  READLINE_POINT=$(( READLINE_POINT + ${#selected} ))
+}
+bind -m emacs-standard -x '"\C-g": __workflowbrowser_widget'
+bind -m vi-command -x '"\C-g": __workflowbrowser_widget'
+bind -m vi-insert -x '"\C-g": __workflowbrowser_widget'
+"#;
+
+const ZSH: &str = r#"# workflowbrowser: press Ctrl-G to pick a workflow and insert it at the cursor
+__workflowbrowser_widget() {
+  local selected
+  selected="$(workflowbrowser --shell zsh < /dev/tty)"
+  if [[ -n "$selected" ]]; then
+    LBUFFER="${LBUFFER}${selected}"
+  fi
+  zle reset-prompt
+}
+zle -N __workflowbrowser_widget
+bindkey -M emacs '^G' __workflowbrowser_widget
+bindkey -M viins '^G' __workflowbrowser_widget
+bindkey -M vicmd '^G' __workflowbrowser_widget
+"#;
+
+const FISH: &str = r#"# workflowbrowser: press Ctrl-G to pick a workflow and insert it at the cursor
+function __workflowbrowser_widget
+    set -l selected (workflowbrowser --shell fish | string collect)
+    if test -n "$selected"
+        commandline --insert -- $selected
+    end
+    commandline --function repaint
+end
+bind \cg __workflowbrowser_widget
+if bind -M insert > /dev/null 2>&1
+    bind -M insert \cg __workflowbrowser_widget
+end
+"#;
+
+impl ShellInitCommand {
+    /// The snippet to source in the rc file of `shell`
+    pub fn script(shell: &Shell) -> &'static str {
+        match shell {
+            Shell::Bash => BASH,
+            Shell::Zsh => ZSH,
+            Shell::Fish => FISH,
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> WorkflowResult<()> {
+        out.write_all(Self::script(&self.shell).as_bytes())?;
+        Ok(())
+    }
+}
+
+impl HandleCommand for ShellInitCommand {
+    fn handle(&self, _services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        self.write(&mut stdout())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::ShellInitCommand;
+    use crate::workflow::file_format::Shell;
+    use std::path::PathBuf;
+
+    /// Compare the snippet for `shell` with its golden file. Run the tests
+    /// with `UPDATE_GOLDEN=1` to accept changed snippets.
+    fn assert_golden(shell: Shell) {
+        let path = PathBuf::from("tests/fixtures/shell-init").join(shell.to_string());
+        let mut out = vec![];
+        ShellInitCommand { shell }.write(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, &out).unwrap();
+        }
+        assert_eq!(out, std::fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn test_bash_snippet() {
+        assert_golden(Shell::Bash);
+    }
+
+    #[test]
+    fn test_zsh_snippet() {
+        assert_golden(Shell::Zsh);
+    }
+
+    #[test]
+    fn test_fish_snippet() {
+        assert_golden(Shell::Fish);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::command::output::{self, Format};
+use crate::command::{argument_values, find_workflow, HandleCommand, ShowCommand};
+use crate::prelude::WorkflowResult;
+use crate::workflow::file_format::{Shell, Workflow};
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+impl ShowCommand {
+    /// The command with the values given with `--arg` or else the default
+    /// values, if every argument has one
+    fn rendered(&self, workflow: &Workflow, shell: &Shell) -> WorkflowResult<Option<String>> {
+        let Some(values) = argument_values(workflow, &self.args, |_| Ok(None))? else {
+            return Ok(None);
+        };
+        let values = values
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        workflow.render_quoted(values, shell).map(Some)
+    }
+
+    fn write(
+        &self,
+        repo: &dyn WorkflowRepository,
+        shell: &Shell,
+        out: &mut impl Write,
+    ) -> WorkflowResult<()> {
+        let workflow = find_workflow(repo, &self.workflow)?;
+        match self.format {
+            Format::Text if self.fields.is_empty() => {
+                write!(out, "{}", workflow)?;
+                if let Some(rendered) = self.rendered(&workflow, shell)? {
+                    writeln!(out)?;
+                    writeln!(out, "Rendered:")?;
+                    for line in rendered.trim_end().lines() {
+                        writeln!(out, "    {}", line)?;
+                    }
+                }
+                Ok(())
+            }
+            format => output::write(&[workflow], format, &self.fields, out),
+        }
+    }
+}
+
+impl HandleCommand for ShowCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+        let repo = repo.borrow();
+        self.write(&*repo, &Shell::current(), &mut stdout().lock())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::output::{Format, OutputField};
+    use crate::command::ShowCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::file_format::Shell;
+    use std::path::PathBuf;
+
+    fn show(workflow: &str, args: &[(&str, &str)], format: Format) -> String {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let command = ShowCommand {
+            workflow: workflow.to_string(),
+            args: args
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            format,
+            fields: match format {
+                Format::Text => vec![],
+                _ => vec![OutputField::Name],
+            },
+        };
+        let mut out = vec![];
+        command.write(&repo, &Shell::Bash, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_show_renders_with_defaults_and_values() {
+        let out = show("local:demo2.yaml", &[], Format::Text);
+        assert!(out.starts_with("Simple Demo\n"));
+        assert!(out.contains("\n  pattern  the pattern to search for (default: *)\n"));
+        assert!(out.ends_with("\nRendered:\n    ls -al '*'\n"));
+
+        // the header and url have no default value
+        let out = show("local:sample.yaml", &[], Format::Text);
+        assert!(!out.contains("Rendered:"));
+        let out = show(
+            "local:sample.yaml",
+            &[("header", "X-A: b"), ("url", "https://a.b")],
+            Format::Text,
+        );
+        assert!(out.contains("\n    curl --header \"X-A: b\" https://a.b\n    demo\n"));
+    }
+
+    #[test]
+    fn test_show_in_other_formats() {
+        assert_eq!(show("local:demo2.yaml", &[], Format::Tsv), "Simple Demo\n");
+    }
+}
@@ -0,0 +1,83 @@
+use crate::command::output;
+use crate::command::{HandleCommand, TagsCommand};
+use crate::prelude::WorkflowResult;
+use crate::workflow::file_format::Workflow;
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::collections::HashMap;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+impl TagsCommand {
+    /// Every tag with the number of workflows having it, most used first
+    fn count(workflows: &[Workflow]) -> Vec<(&str, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for workflow in workflows {
+            let mut tags: Vec<&str> = workflow.tags.iter().map(String::as_str).collect();
+            tags.sort();
+            tags.dedup();
+            for tag in tags {
+                *counts.entry(tag).or_default() += 1;
+            }
+        }
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        counts
+    }
+
+    fn write(&self, repo: &dyn WorkflowRepository, out: &mut impl Write) -> WorkflowResult<()> {
+        let workflows = repo.get_workflows()?;
+        output::write_tags(&Self::count(&workflows), self.format, out)
+    }
+}
+
+impl HandleCommand for TagsCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+        let repo = repo.borrow();
+        self.write(&*repo, &mut stdout().lock())?;
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::output::Format;
+    use crate::command::TagsCommand;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::workflow::file_format::Workflow;
+    use std::path::PathBuf;
+
+    fn tagged(tags: &[&str]) -> Workflow {
+        let mut workflow = Workflow::new("a", "b");
+        workflow.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        workflow
+    }
+
+    #[test]
+    fn test_count_tags() {
+        let workflows = vec![
+            tagged(&["git", "undo"]),
+            tagged(&["git", "git"]),
+            tagged(&["docker"]),
+        ];
+        assert_eq!(
+            TagsCommand::count(&workflows),
+            vec![("git", 2), ("docker", 1), ("undo", 1)]
+        );
+    }
+
+    #[test]
+    fn test_write_tags() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let mut out = vec![];
+        let command = TagsCommand {
+            format: Format::Tsv,
+        };
+        command.write(&repo, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "files\t2\nls\t2\ncurl\t1\n"
+        );
+    }
+}
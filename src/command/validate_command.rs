@@ -0,0 +1,77 @@
+use crate::command::{HandleCommand, ValidateCommand};
+use crate::prelude::WorkflowResult;
+use crate::workflow::diagnostic::Diagnostic;
+use crate::workflow::repository::WorkflowRepository;
+use di::ServiceProvider;
+use std::io::{stdout, Write};
+use std::process::ExitCode;
+
+impl ValidateCommand {
+    /// Write one line per invalid workflow file to `out` and return the
+    /// number of invalid files. Files that load but whose command does not
+    /// fit the arguments, or with arguments that make no sense, are invalid
+    /// as well.
+    fn report(repo: &dyn WorkflowRepository, out: &mut impl Write) -> WorkflowResult<usize> {
+        let mut diagnostics = repo.diagnostics();
+        for workflow in repo.get_workflows()? {
+            if let (Err(error), Some(source)) = (workflow.check(), &workflow.source) {
+                diagnostics.push(Diagnostic::new(&source.file, error));
+            }
+        }
+        for diagnostic in &diagnostics {
+            writeln!(out, "{}", diagnostic)?;
+        }
+        Ok(diagnostics.len())
+    }
+}
+
+impl HandleCommand for ValidateCommand {
+    fn handle(&self, services: &ServiceProvider) -> WorkflowResult<ExitCode> {
+        let repo = services.get_required_mut::<dyn WorkflowRepository>();
+
+        let repo = repo.borrow();
+        match Self::report(&*repo, &mut stdout())? {
+            0 => Ok(ExitCode::SUCCESS),
+            invalid => {
+                eprintln!("{} invalid workflow file(s)", invalid);
+                Ok(ExitCode::FAILURE)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::command::ValidateCommand;
+    use crate::workflow::repository::directory::DirectoryRepository;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_report_lists_invalid_files() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/invalid"));
+        let mut out = vec![];
+
+        assert_eq!(ValidateCommand::report(&repo, &mut out).unwrap(), 2);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("broken_syntax.yaml:"));
+        assert!(out.contains("missing_command.yaml:"));
+        assert!(out.contains("missing field `command`"));
+
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        assert_eq!(ValidateCommand::report(&repo, &mut vec![]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_report_lists_broken_templates() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/templates"));
+        let mut out = vec![];
+
+        assert_eq!(ValidateCommand::report(&repo, &mut out).unwrap(), 2);
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.contains("enum_without_options.yaml: invalid argument: enum mode has no options")
+        );
+        assert!(out.contains("unknown_placeholder.yaml: invalid command template: "));
+        assert!(out.contains("unknown placeholder(s) {{uri}}; unused argument(s) url"));
+    }
+}
@@ -0,0 +1,313 @@
+use crate::prelude::composite::CompositeRepository;
+use crate::prelude::directory::DirectoryRepository;
+use crate::prelude::git::{id_from_url, GitRepository};
+use crate::prelude::repository::WorkflowRepository;
+use crate::prelude::{WorkflowError, WorkflowResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const APP_NAME: &str = "workflowbrowser";
+
+/// A place workflows are loaded from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceConfig {
+    /// Workflow YAML files below a local directory
+    Directory { id: Option<String>, path: PathBuf },
+    /// A git remote, checked out into a local directory
+    Git {
+        id: Option<String>,
+        url: String,
+        #[serde(default = "default_branch")]
+        branch: String,
+        checkout: Option<PathBuf>,
+    },
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+impl SourceConfig {
+    /// The id workflows of this source are identified by
+    pub fn id(&self) -> String {
+        match self {
+            SourceConfig::Directory { id, .. } => id.clone().unwrap_or("local".to_string()),
+            SourceConfig::Git { id, url, .. } => id.clone().unwrap_or_else(|| id_from_url(url)),
+        }
+    }
+
+    /// Create the repository described by this source. Missing local
+    /// directories are created, git remotes are cloned if not checked out yet.
+    pub fn open(&self) -> WorkflowResult<Box<dyn WorkflowRepository>> {
+        match self {
+            SourceConfig::Directory { path, .. } => {
+                let path = expand_home(path);
+                std::fs::create_dir_all(&path).map_err(|e| WorkflowError::io(&path, e))?;
+                Ok(Box::new(DirectoryRepository::new(path).with_id(self.id())))
+            }
+            SourceConfig::Git {
+                url,
+                branch,
+                checkout,
+                ..
+            } => {
+                let checkout = checkout
+                    .as_ref()
+                    .map(|path| expand_home(path))
+                    .unwrap_or_else(|| cache_dir().join(self.id()));
+                if let Some(parent) = checkout.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| WorkflowError::io(parent, e))?;
+                }
+                Ok(Box::new(
+                    GitRepository::new(url, branch, checkout)?.with_id(self.id()),
+                ))
+            }
+        }
+    }
+}
+
+/// Settings read from `$XDG_CONFIG_HOME/workflowbrowser/config.yaml`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Workflow sources, the first one has the highest priority
+    #[serde(default)]
+    pub sources: Vec<SourceConfig>,
+    /// Id of the source new and edited workflows are saved to,
+    /// defaults to the first source
+    pub write_target: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            sources: vec![
+                SourceConfig::Directory {
+                    id: None,
+                    path: data_dir().join("workflows"),
+                },
+                SourceConfig::Git {
+                    id: None,
+                    url: "https://github.com/warpdotdev/workflows.git".to_string(),
+                    branch: default_branch(),
+                    checkout: None,
+                },
+            ],
+            write_target: None,
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file
+    pub fn path() -> PathBuf {
+        xdg_dir("XDG_CONFIG_HOME", ".config")
+            .join(APP_NAME)
+            .join("config.yaml")
+    }
+
+    /// Load the config file, falling back to the defaults if there is none
+    pub fn load() -> WorkflowResult<Self> {
+        let path = Self::path();
+        if path.exists() {
+            Self::load_from(&path)
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    pub fn load_from(path: &Path) -> WorkflowResult<Self> {
+        let file = std::fs::File::open(path).map_err(|e| WorkflowError::io(path, e))?;
+        serde_yaml::from_reader(std::io::BufReader::new(file))
+            .map_err(|e| WorkflowError::parse(path, e))
+    }
+
+    /// Check that the source ids are unique and the write target is one
+    /// of them, without opening any source
+    pub fn check(&self) -> WorkflowResult<()> {
+        let mut ids: Vec<String> = vec![];
+        for source in &self.sources {
+            let id = source.id();
+            if ids.contains(&id) {
+                return Err(WorkflowError::Conflict(format!(
+                    "source id {} is used more than once",
+                    id
+                )));
+            }
+            ids.push(id);
+        }
+        match &self.write_target {
+            Some(target) if !ids.contains(target) => Err(WorkflowError::InvalidArgument(format!(
+                "write target {} is not a configured source",
+                target
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Open a repository for every source, in priority order. Sources that
+    /// fail to open are skipped and reported by [`CompositeRepository::errors`].
+    /// The ids are not checked again, that is up to [`Config::check`].
+    pub fn open(&self) -> CompositeRepository {
+        let mut repository = CompositeRepository::new(vec![]);
+        if let Some(target) = &self.write_target {
+            repository = repository.with_write_target(target);
+        }
+        for source in &self.sources {
+            match source.open() {
+                Ok(opened) => repository.push(opened),
+                Err(error) => repository.push_error(source.id(), error),
+            }
+        }
+        repository
+    }
+}
+
+/// Resolve an XDG base directory, falling back to `$HOME/<fallback>`
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .unwrap_or_else(|| PathBuf::from(fallback))
+}
+
+fn data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share").join(APP_NAME)
+}
+
+fn cache_dir() -> PathBuf {
+    xdg_dir("XDG_CACHE_HOME", ".cache").join(APP_NAME)
+}
+
+/// Replace a leading `~` by the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, SourceConfig};
+    use crate::workflow::repository::WorkflowRepository;
+    use crate::workflow::source::WorkflowId;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_sources_in_order() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+sources:
+  - type: directory
+    path: ~/workflows
+  - type: git
+    id: team
+    url: https://git.example.com/team/workflows.git
+    checkout: /tmp/team
+  - type: git
+    url: https://github.com/warpdotdev/workflows.git
+    branch: develop
+write_target: local
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.sources,
+            vec![
+                SourceConfig::Directory {
+                    id: None,
+                    path: PathBuf::from("~/workflows"),
+                },
+                SourceConfig::Git {
+                    id: Some("team".to_string()),
+                    url: "https://git.example.com/team/workflows.git".to_string(),
+                    branch: "main".to_string(),
+                    checkout: Some(PathBuf::from("/tmp/team")),
+                },
+                SourceConfig::Git {
+                    id: None,
+                    url: "https://github.com/warpdotdev/workflows.git".to_string(),
+                    branch: "develop".to_string(),
+                    checkout: None,
+                },
+            ]
+        );
+        let ids: Vec<String> = config.sources.iter().map(|s| s.id()).collect();
+        assert_eq!(ids, vec!["local", "team", "workflows"]);
+        assert_eq!(config.write_target, Some("local".to_string()));
+    }
+
+    #[test]
+    fn test_directory_sources_become_repositories() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            sources: vec![
+                SourceConfig::Directory {
+                    id: Some("fixtures".to_string()),
+                    path: PathBuf::from("tests/fixtures/workflows"),
+                },
+                SourceConfig::Directory {
+                    id: None,
+                    path: dir.path().join("not-yet-created"),
+                },
+            ],
+            write_target: None,
+        };
+
+        let repository = config.open();
+        assert!(repository.errors().is_empty());
+        assert!(repository
+            .get_workflow(&WorkflowId::new("fixtures", "sample.yaml"))
+            .is_ok());
+        assert_eq!(repository.get_workflows().unwrap().len(), 3);
+        assert!(dir.path().join("not-yet-created").is_dir());
+    }
+
+    #[test]
+    fn test_sources_failing_to_open_are_reported() {
+        let dir = TempDir::new().unwrap();
+        let config = Config {
+            sources: vec![
+                SourceConfig::Git {
+                    id: None,
+                    url: dir.path().join("missing.git").display().to_string(),
+                    branch: "main".to_string(),
+                    checkout: Some(dir.path().join("checkout")),
+                },
+                SourceConfig::Directory {
+                    id: None,
+                    path: PathBuf::from("tests/fixtures/workflows"),
+                },
+            ],
+            write_target: None,
+        };
+
+        let repository = config.open();
+        assert_eq!(repository.errors().len(), 1);
+        assert_eq!(repository.errors()[0].source, "missing");
+        assert_eq!(repository.get_workflows().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_duplicate_source_ids_are_rejected() {
+        let source = SourceConfig::Directory {
+            id: None,
+            path: PathBuf::from("tests/fixtures/workflows"),
+        };
+        let config = Config {
+            sources: vec![source.clone(), source],
+            write_target: None,
+        };
+        assert!(config.check().is_err());
+
+        let config = Config {
+            sources: vec![],
+            write_target: Some("local".to_string()),
+        };
+        assert!(config.check().is_err());
+    }
+}
@@ -1,89 +1,193 @@
-use std::path::PathBuf;
-// use clap::Parser;
+use clap::Parser;
 
-// use crate::command::{Cli, Commands, HandleCommand};
+use crate::command::{Cli, HandleCommand};
 
 use crossterm::{
-    event::{self},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use ratatui::layout::Rect;
+use di::{existing_as_self, singleton_factory, Mut, Ref, ServiceCollection, ServiceProvider};
 use ratatui::prelude::{CrosstermBackend, Terminal};
-use std::io::{stdout, Result};
+use std::io::{stderr, stdout, Error, Result, Write};
+use std::path::Path;
+use std::process::ExitCode;
 
+use crate::config::Config;
+use crate::prelude::repository::WorkflowRepository;
 use crate::ui::components::Action;
-use crate::ui::components::Action::Quit;
-use crate::ui::Focus::List;
+use crate::ui::components::Action::{Quit, Select};
 use crate::ui::{AppState, Focus};
+use crate::workflow::file_format::Shell;
 
 mod command;
+mod config;
 mod prelude;
 mod workflow;
 
 mod ui;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
+    let services = match Config::load().map_err(Error::other).and_then(services) {
+        Ok(services) => services,
+        Err(error) => {
+            eprintln!("{}", error);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    if let Some(command) = &cli.command {
+        return Ok(command.handle(&services).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }));
+    }
     // run and save results
-    let result = run();
+    let result = run(&services, cli.shell);
     // make sure to call shutdown
     shutdown_terminal()?;
-    // and finally return the result
-    result?;
-    Ok(())
+    // and finally hand the selected command to the caller, e.g. a shell
+    // widget putting it into the command line
+    if let Some(command) = result? {
+        write_command(&command, cli.output.as_deref(), &mut stdout())?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Write `command` to the file `output`, or to `out` without one
+fn write_command(command: &str, output: Option<&Path>, out: &mut impl Write) -> Result<()> {
+    match output {
+        Some(path) => std::fs::write(path, command),
+        None => writeln!(out, "{}", command),
+    }
+}
+
+/// Register `config` and all workflow sources as one mutable
+/// `dyn WorkflowRepository`. The sources are opened when first asked for,
+/// commands that need none of them do not wait for git remotes.
+fn services(config: Config) -> Result<ServiceProvider> {
+    config.check().map_err(Error::other)?;
+    ServiceCollection::new()
+        .add(existing_as_self(config))
+        .add(singleton_factory(|services| {
+            let config = services.get_required::<Config>();
+            // sources that fail to open are left out and reported
+            let repository = config.open();
+            for error in repository.errors() {
+                eprintln!("unable to load {}: {}", error.source, error.error);
+            }
+            let repository: Ref<Mut<dyn WorkflowRepository>> = Ref::new(Mut::new(repository));
+            repository
+        }))
+        .build_provider()
+        .map_err(Error::other)
 }
 
-fn run() -> Result<()> {
-    // create app state holding git_repo and local dir_repo
-    let mut state = AppState::new(
-        "https://github.com/warpdotdev/workflows.git",
-        "main",
-        PathBuf::from("tests/fixtures/github/warpdotdev"),
-        PathBuf::from("tests/fixtures/workflows"),
-    );
+/// Run the TUI until it is quit, returning the command the user selected
+fn run(services: &ServiceProvider, shell: Option<Shell>) -> Result<Option<String>> {
+    // create app state holding all configured workflow sources
+    let mut state = AppState::new(services.get_required_mut::<dyn WorkflowRepository>());
+    state.shell = shell;
 
     init_terminal()?;
 
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    // the TUI is drawn on stderr, keeping stdout free for the command
+    let mut terminal = Terminal::new(CrosstermBackend::new(stderr()))?;
     terminal.clear()?;
     loop {
-        terminal.draw(|frame|
-            match state.focus {
-            Focus::List => state
+        terminal.draw(|frame| {
+            // the form and the editor are drawn on top of the list
+            state
                 .command_list_component
-                .render(&state, frame, Rect::default()),
+                .render(&state, frame, frame.size());
+            if let Some(form) = &state.argument_form_component {
+                form.render(&state, frame, frame.size());
+            }
+            if let Some(editor) = &state.workflow_editor_component {
+                editor.render(&state, frame, frame.size());
+            }
         })?;
 
-        let action = update(&mut state);
-
-        if let Quit = action {
-            break;
+        match update(&mut state) {
+            Quit => break,
+            Select(command) => return Ok(Some(command)),
+            _ => {}
         }
     }
-    Ok(())
+    Ok(None)
 }
 
 fn update(app_state: &mut AppState) -> Action {
     if event::poll(std::time::Duration::from_millis(16)).unwrap() {
-        match app_state.focus {
-            List => {
-                if let Ok(event) = event::read() {
-                    return app_state.command_list_component.handle_events(Some(event));
-                }
-            }
+        if let Ok(event) = event::read() {
+            let action = match (
+                &app_state.focus,
+                &mut app_state.argument_form_component,
+                &mut app_state.workflow_editor_component,
+            ) {
+                (Focus::Form, Some(form), _) => form.handle_events(Some(event)),
+                (Focus::Editor, _, Some(editor)) => editor.handle_events(Some(event)),
+                _ => app_state.command_list_component.handle_events(Some(event)),
+            };
+            return app_state.apply(action);
         }
     }
     Action::None
 }
 
 fn shutdown_terminal() -> Result<()> {
-    stdout().execute(LeaveAlternateScreen)?;
+    stderr().execute(DisableBracketedPaste)?;
+    stderr().execute(LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
 }
 
 fn init_terminal() -> Result<()> {
     enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
+    stderr().execute(EnterAlternateScreen)?;
+    stderr().execute(EnableBracketedPaste)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{Config, SourceConfig};
+    use crate::prelude::repository::WorkflowRepository;
+    use crate::{services, write_command};
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_services_provide_the_configured_sources() {
+        let mut config = Config {
+            sources: vec![SourceConfig::Directory {
+                id: Some("fixtures".to_string()),
+                path: PathBuf::from("tests/fixtures/workflows"),
+            }],
+            write_target: None,
+        };
+        let provider = services(config.clone()).unwrap();
+        let repo = provider.get_required_mut::<dyn WorkflowRepository>();
+        assert_eq!(repo.borrow().get_workflows().unwrap().len(), 3);
+        // the same repository every time
+        let again = provider.get_required_mut::<dyn WorkflowRepository>();
+        assert!(std::rc::Rc::ptr_eq(&repo, &again));
+
+        config.write_target = Some("nope".to_string());
+        assert!(services(config).is_err());
+    }
+
+    #[test]
+    fn test_write_command() {
+        let mut out = vec![];
+        write_command("git status", None, &mut out).unwrap();
+        assert_eq!(out, b"git status\n");
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("command");
+        let mut out = vec![];
+        write_command("git status", Some(&path), &mut out).unwrap();
+        assert!(out.is_empty());
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "git status");
+    }
+}
@@ -1,16 +1,121 @@
 pub use crate::workflow::repository;
+pub use crate::workflow::repository::composite;
 pub use crate::workflow::repository::directory;
 pub use crate::workflow::repository::git;
 
-#[derive(Debug)]
+use crate::workflow::repository::composite::SourceError;
+use crate::workflow::repository::ChangeSet;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone)]
 pub enum WorkflowError {
+    /// A workflow or config file is no valid YAML or misses fields
+    Parse { path: PathBuf, message: String },
+    /// A git operation failed, `path` is the checkout it failed on
+    Git {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// Reading or writing a file failed
+    Io {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// A workflow or workflow source does not exist
     NotFound(String),
-    IoError(String),
+    /// The operation clashes with the current state, e.g. a diverged branch
+    Conflict(String),
+    /// A value passed in by the user is invalid
+    InvalidArgument(String),
+    /// The command of a workflow does not fit its arguments
+    Template(String),
+    /// Some sources failed to refresh, the others were refreshed with
+    /// `changes`
+    Refresh {
+        changes: ChangeSet,
+        errors: Vec<SourceError>,
+    },
+}
+
+impl WorkflowError {
+    pub fn parse(path: impl Into<PathBuf>, message: impl Display) -> Self {
+        WorkflowError::Parse {
+            path: path.into(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn git(path: impl Into<PathBuf>, message: impl Display) -> Self {
+        WorkflowError::Git {
+            path: Some(path.into()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn io(path: impl Into<PathBuf>, message: impl Display) -> Self {
+        WorkflowError::Io {
+            path: Some(path.into()),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for WorkflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkflowError::Parse { path, message } => {
+                write!(f, "unable to parse {}: {}", path.display(), message)
+            }
+            WorkflowError::Git {
+                path: Some(path),
+                message,
+            } => write!(f, "git error in {}: {}", path.display(), message),
+            WorkflowError::Git {
+                path: None,
+                message,
+            } => write!(f, "git error: {}", message),
+            WorkflowError::Io {
+                path: Some(path),
+                message,
+            } => write!(f, "{}: {}", path.display(), message),
+            WorkflowError::Io {
+                path: None,
+                message,
+            } => write!(f, "{}", message),
+            WorkflowError::NotFound(what) => write!(f, "{} not found", what),
+            WorkflowError::Conflict(message) => write!(f, "conflict: {}", message),
+            WorkflowError::InvalidArgument(message) => write!(f, "invalid argument: {}", message),
+            WorkflowError::Template(message) => write!(f, "invalid command template: {}", message),
+            WorkflowError::Refresh { errors, .. } => {
+                let errors: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.source, e.error))
+                    .collect();
+                write!(f, "unable to refresh {}", errors.join("; "))
+            }
+        }
+    }
 }
 
+impl std::error::Error for WorkflowError {}
+
 impl From<std::io::Error> for WorkflowError {
     fn from(value: std::io::Error) -> Self {
-        WorkflowError::IoError(format!("{:?}", value))
+        WorkflowError::Io {
+            path: None,
+            message: value.to_string(),
+        }
+    }
+}
+
+impl From<git2::Error> for WorkflowError {
+    fn from(value: git2::Error) -> Self {
+        WorkflowError::Git {
+            path: None,
+            message: value.message().to_string(),
+        }
     }
 }
 
@@ -18,3 +123,22 @@ pub type WorkflowResult<T> = Result<T, WorkflowError>;
 #[allow(dead_code)]
 pub struct W<A>(A);
 
+#[cfg(test)]
+mod tests {
+    use crate::prelude::WorkflowError;
+
+    #[test]
+    fn test_errors_name_the_offending_path() {
+        let error = WorkflowError::parse("workflows/broken.yaml", "missing field `command`");
+        assert_eq!(
+            error.to_string(),
+            "unable to parse workflows/broken.yaml: missing field `command`"
+        );
+        let error = WorkflowError::git("checkout", "not a repository");
+        assert_eq!(error.to_string(), "git error in checkout: not a repository");
+        assert_eq!(
+            WorkflowError::NotFound("local:demo.yaml".to_string()).to_string(),
+            "local:demo.yaml not found"
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use crate::prelude::WorkflowResult;
+use crate::ui::components::text_input::TextInput;
+use crate::ui::components::{Action, Component};
+use crate::ui::AppState;
+use crate::workflow::file_format::{Argument, ArgumentType, Shell, Workflow};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use std::collections::HashMap;
+
+// rows of a single argument input, including its border
+const FIELD_HEIGHT: u16 = 3;
+// rows of the rendered command, including its border
+const PREVIEW_HEIGHT: u16 = 5;
+
+/// The input of a single argument
+enum Input {
+    Text(TextInput),
+    /// a bool argument, flipped with Space, Left or Right
+    Toggle(bool),
+    /// an enum argument, the index of the selected option
+    Select(usize),
+}
+
+impl Input {
+    fn new(argument: &Argument) -> Self {
+        let default_value = argument.default_value.as_deref();
+        match argument.kind {
+            ArgumentType::Bool => Input::Toggle(default_value == Some("true")),
+            ArgumentType::Enum if !argument.options.is_empty() => Input::Select(
+                argument
+                    .options
+                    .iter()
+                    .position(|o| Some(o.as_str()) == default_value)
+                    .unwrap_or(0),
+            ),
+            _ => {
+                let mut input = TextInput::new();
+                input.insert_str(default_value.unwrap_or_default());
+                Input::Text(input)
+            }
+        }
+    }
+
+    fn value<'a>(&'a self, argument: &'a Argument) -> &'a str {
+        match self {
+            Input::Text(input) => input.value(),
+            Input::Toggle(true) => "true",
+            Input::Toggle(false) => "false",
+            Input::Select(selected) => &argument.options[*selected],
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, argument: &Argument) {
+        let options = argument.options.len();
+        match (self, key.code) {
+            (Input::Text(input), _) => {
+                input.handle_key(key);
+            }
+            (Input::Toggle(on), KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right) => *on = !*on,
+            (Input::Select(selected), KeyCode::Char(' ') | KeyCode::Right) => {
+                *selected = (*selected + 1) % options
+            }
+            (Input::Select(selected), KeyCode::Left) => {
+                *selected = (*selected + options - 1) % options
+            }
+            _ => {}
+        }
+    }
+
+    /// The line shown in the field and the column of the cursor, if the
+    /// input has one
+    fn line<'a>(&'a self, argument: &'a Argument, width: usize) -> (Line<'a>, Option<usize>) {
+        match self {
+            Input::Text(input) => {
+                let (visible, cursor) = input.visible(width);
+                (Line::raw(visible), Some(cursor))
+            }
+            Input::Toggle(on) => {
+                let check = if *on { "[x] " } else { "[ ] " };
+                (Line::raw(format!("{}{}", check, on)), None)
+            }
+            Input::Select(selected) => {
+                let mut spans = vec![];
+                for (i, option) in argument.options.iter().enumerate() {
+                    if i > 0 {
+                        spans.push(Span::raw(" "));
+                    }
+                    let style = match i == *selected {
+                        true => Style::default().add_modifier(Modifier::REVERSED),
+                        false => Style::default(),
+                    };
+                    spans.push(Span::styled(format!(" {} ", option), style));
+                }
+                (Line::from(spans), None)
+            }
+        }
+    }
+}
+
+/// The shell to quote values for: `shell` the command is inserted into if
+/// it is known, the only shell the workflow is for or else `$SHELL`
+fn quoting_shell(workflow: &Workflow, shell: Option<Shell>) -> Shell {
+    shell
+        .or_else(|| match workflow.shells.as_slice() {
+            [only] => Some(only.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(Shell::current)
+}
+
+/// A form with an input per argument of a workflow, showing the command
+/// as it will be rendered while the arguments are filled in
+pub struct ArgumentFormComponent {
+    workflow: Workflow,
+    // one input per argument, in the order of the arguments
+    inputs: Vec<Input>,
+    focused: usize,
+    // the values are quoted for the shell the command is inserted into
+    shell: Shell,
+}
+
+impl ArgumentFormComponent {
+    /// Start with the default values of the arguments, quoted for `shell`
+    /// if the shell the command is inserted into is known
+    pub fn new(workflow: Workflow, shell: Option<Shell>) -> Box<Self> {
+        let inputs = workflow.arguments.iter().map(Input::new).collect();
+        let shell = quoting_shell(&workflow, shell);
+        Box::new(ArgumentFormComponent {
+            workflow,
+            inputs,
+            focused: 0,
+            shell,
+        })
+    }
+
+    pub fn values(&self) -> HashMap<&str, &str> {
+        self.workflow
+            .arguments
+            .iter()
+            .zip(self.inputs.iter())
+            .map(|(argument, input)| (argument.name.as_str(), input.value(argument)))
+            .collect()
+    }
+
+    /// The command with the current values filled in, if they are valid
+    pub fn rendered(&self) -> WorkflowResult<String> {
+        let values = self.values();
+        for argument in &self.workflow.arguments {
+            argument.validate(values[argument.name.as_str()])?;
+        }
+        self.workflow.render_quoted(values, &self.shell)
+    }
+
+    fn focus_next(&mut self) {
+        if !self.inputs.is_empty() {
+            self.focused = (self.focused + 1) % self.inputs.len();
+        }
+    }
+
+    fn focus_previous(&mut self) {
+        if !self.inputs.is_empty() {
+            self.focused = (self.focused + self.inputs.len() - 1) % self.inputs.len();
+        }
+    }
+}
+
+/// Center the form in `area`, as high as its fields need if there is room
+fn popup_area(area: Rect, fields: usize) -> Rect {
+    let width = area.width * 4 / 5;
+    let height = (fields as u16 * FIELD_HEIGHT + PREVIEW_HEIGHT + 2).min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+impl Component for ArgumentFormComponent {
+    fn render(&self, _state: &AppState, frame: &mut Frame, area: Rect) {
+        let area = popup_area(area, self.inputs.len());
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", self.workflow.name))
+            .title(
+                Title::from(" Tab: next field, Enter: accept, Esc: back ")
+                    .position(Position::Bottom),
+            );
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let mut constraints = vec![Constraint::Length(FIELD_HEIGHT); self.inputs.len()];
+        constraints.push(Constraint::Min(PREVIEW_HEIGHT));
+        let layout = Layout::default().constraints(constraints).split(inner);
+
+        let fields = self.workflow.arguments.iter().zip(self.inputs.iter());
+        for (i, (argument, input)) in fields.enumerate() {
+            let field = layout[i];
+            let title = match &argument.description {
+                Some(description) => format!(" {}: {} ", argument.name, description),
+                None => format!(" {} ", argument.name),
+            };
+            let error = argument.validate(input.value(argument)).err();
+            let style = match (i == self.focused, &error) {
+                (_, Some(_)) => Style::default().fg(Color::Red),
+                (true, None) => Style::default().fg(Color::Yellow),
+                (false, None) => Style::default(),
+            };
+            let mut block = Block::new()
+                .borders(Borders::ALL)
+                .border_style(style)
+                .title(title);
+            if let Some(error) = error {
+                block = block.title(
+                    Title::from(format!(" {} ", error))
+                        .position(Position::Bottom)
+                        .alignment(Alignment::Right),
+                );
+            }
+            let (line, cursor) = input.line(argument, field.width.saturating_sub(2) as usize);
+            frame.render_widget(Paragraph::new(line).block(block), field);
+            if let (true, Some(cursor)) = (i == self.focused, cursor) {
+                frame.set_cursor(field.x + 1 + cursor as u16, field.y + 1);
+            }
+        }
+
+        let command = match self.rendered() {
+            Ok(command) => Paragraph::new(command),
+            Err(error) => Paragraph::new(error.to_string()).style(Style::default().fg(Color::Red)),
+        };
+        frame.render_widget(
+            command
+                .wrap(Wrap { trim: false })
+                .block(Block::new().borders(Borders::ALL).title(" Command ")),
+            layout[self.inputs.len()],
+        );
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Quit,
+            KeyCode::Enter => match self.rendered() {
+                Ok(command) => Action::Select(command),
+                Err(_) => Action::None,
+            },
+            KeyCode::Esc => Action::Back,
+            KeyCode::Tab | KeyCode::Down => {
+                self.focus_next();
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus_previous();
+                Action::None
+            }
+            _ => {
+                if let Some(input) = self.inputs.get_mut(self.focused) {
+                    input.handle_key(key, &self.workflow.arguments[self.focused]);
+                }
+                Action::None
+            }
+        }
+    }
+
+    fn handle_paste_event(&mut self, text: String) -> Action {
+        if let Some(Input::Text(input)) = self.inputs.get_mut(self.focused) {
+            input.insert_str(&text);
+        }
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::composite::CompositeRepository;
+    use crate::ui::components::argument_form::{quoting_shell, ArgumentFormComponent};
+    use crate::ui::components::{Action, Component};
+    use crate::ui::AppState;
+    use crate::workflow::file_format::{Argument, ArgumentType, Shell, Workflow};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn press(form: &mut ArgumentFormComponent, code: KeyCode) -> Action {
+        form.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn form() -> Box<ArgumentFormComponent> {
+        let mut count = Argument::new("count");
+        count.default_value = Some("1".to_string());
+        ArgumentFormComponent::new(
+            Workflow::new("Undo", "git reset {{mode}} HEAD~{{count}}")
+                .with_arguments(vec![count, Argument::new("mode")]),
+            Some(Shell::Bash),
+        )
+    }
+
+    #[test]
+    fn test_fields_start_with_defaults() {
+        let form = form();
+        assert_eq!(form.values()["count"], "1");
+        assert_eq!(form.values()["mode"], "");
+        assert_eq!(form.rendered().unwrap(), "git reset '' HEAD~1");
+    }
+
+    #[test]
+    fn test_tab_moves_between_fields() {
+        let mut form = form();
+        press(&mut form, KeyCode::Tab);
+        for c in "--soft".chars() {
+            press(&mut form, KeyCode::Char(c));
+        }
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Backspace);
+        press(&mut form, KeyCode::Char('3'));
+        press(&mut form, KeyCode::BackTab);
+        press(&mut form, KeyCode::BackTab);
+        assert_eq!(form.focused, 0);
+
+        assert_eq!(
+            press(&mut form, KeyCode::Enter),
+            Action::Select("git reset --soft HEAD~3".to_string())
+        );
+        assert_eq!(press(&mut form, KeyCode::Esc), Action::Back);
+    }
+
+    #[test]
+    fn test_typed_fields() {
+        let options = vec!["soft".to_string(), "mixed".to_string(), "hard".to_string()];
+        let mut mode = Argument::new("mode")
+            .with_type(ArgumentType::Enum)
+            .with_options(options);
+        mode.default_value = Some("mixed".to_string());
+        let mut count = Argument::new("count").with_type(ArgumentType::Int);
+        count.default_value = Some("1".to_string());
+        let mut form = ArgumentFormComponent::new(
+            Workflow::new("Undo", "git reset --{{mode}} HEAD~{{count}} {{quiet}}").with_arguments(
+                vec![
+                    mode,
+                    count,
+                    Argument::new("quiet").with_type(ArgumentType::Bool),
+                ],
+            ),
+            Some(Shell::Bash),
+        );
+        assert_eq!(form.values()["mode"], "mixed");
+        assert_eq!(form.values()["quiet"], "false");
+
+        press(&mut form, KeyCode::Right);
+        press(&mut form, KeyCode::Right);
+        assert_eq!(form.values()["mode"], "soft");
+        press(&mut form, KeyCode::Left);
+        assert_eq!(form.values()["mode"], "hard");
+
+        press(&mut form, KeyCode::BackTab);
+        press(&mut form, KeyCode::Char(' '));
+        assert_eq!(form.values()["quiet"], "true");
+
+        press(&mut form, KeyCode::BackTab);
+        press(&mut form, KeyCode::Char('x'));
+        assert_eq!(press(&mut form, KeyCode::Enter), Action::None);
+        assert!(form.rendered().is_err());
+        press(&mut form, KeyCode::Backspace);
+        assert_eq!(
+            press(&mut form, KeyCode::Enter),
+            Action::Select("git reset --hard HEAD~1 true".to_string())
+        );
+    }
+
+    #[test]
+    fn test_render_shows_the_rendered_command() {
+        let state = AppState::new(Rc::new(RefCell::new(CompositeRepository::default())));
+        let form = form();
+        let mut terminal = Terminal::new(TestBackend::new(50, 20)).unwrap();
+        terminal
+            .draw(|frame| form.render(&state, frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..20)
+            .flat_map(|y| (0..50).map(move |x| (x, y)))
+            .map(|(x, y)| buffer.get(x, y).symbol())
+            .collect();
+        assert!(screen.contains(" Undo "));
+        assert!(screen.contains(" count "));
+        assert!(screen.contains("git reset '' HEAD~1"));
+    }
+
+    #[test]
+    fn test_quoting_shell() {
+        let mut workflow = Workflow::new("Greet", "echo {{name}}");
+        workflow.shells = vec![Shell::Fish];
+        assert_eq!(quoting_shell(&workflow, None), Shell::Fish);
+        assert_eq!(quoting_shell(&workflow, Some(Shell::Zsh)), Shell::Zsh);
+    }
+}
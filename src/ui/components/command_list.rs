@@ -1,71 +1,498 @@
+use crate::prelude::repository::WorkflowRepository;
+use crate::ui::components::preview::WorkflowPreview;
+use crate::ui::components::text_input::TextInput;
 use crate::ui::components::{Action, Component};
 use crate::ui::AppState;
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
-use ratatui::buffer::Buffer;
-use ratatui::layout::{Constraint, Margin, Rect};
-use ratatui::widgets::{Block, Borders, List, Paragraph};
+use crate::workflow::diagnostic::Diagnostic;
+use crate::workflow::file_format::Workflow;
+use crate::workflow::search::{Field, WorkflowMatch};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::block::Title;
+use ratatui::widgets::{
+    Block, Borders, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
+    ScrollbarState,
+};
 use ratatui::Frame;
-use ratatui::prelude::{Layout, Widget};
-use crate::main;
-use crate::prelude::repository::WorkflowRepository;
+use std::cell::{Cell, RefCell};
+
+const PROMPT: &str = "> ";
+
+/// `text` with the chars at `positions` highlighted
+fn highlight<'a>(text: &str, positions: &[usize]) -> Line<'a> {
+    let matched = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+    let spans: Vec<Span> = text
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match positions.contains(&i) {
+            true => Span::styled(c.to_string(), matched),
+            false => Span::raw(c.to_string()),
+        })
+        .collect();
+    Line::from(spans)
+}
 
-pub struct CommandListComponent;
+fn list_item<'a>(found: &WorkflowMatch) -> ListItem<'a> {
+    let workflow = &found.workflow;
+    let description = workflow.description.as_deref().unwrap_or_default();
+    ListItem::new(Text::from(vec![
+        Line::raw("------------------------------------------"),
+        highlight(&workflow.name, found.positions(Field::Name)),
+        highlight(description, found.positions(Field::Description)),
+        Line::raw("------------------------------------------"),
+    ]))
+}
+
+/// What the repository had for a query, kept until the query or the
+/// workflows change
+#[derive(Default)]
+struct Listing {
+    query: String,
+    // the revision of the app state it was made at, none before the first
+    revision: Option<usize>,
+    matches: Vec<WorkflowMatch>,
+    // number of workflows in all sources
+    total: usize,
+    // why there are no matches if the repository failed
+    error: Option<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Listing {
+    fn new(state: &AppState, query: &str) -> Self {
+        let found = state.query_workflows(query).and_then(|matches| {
+            let total = match query.trim().is_empty() {
+                true => matches.len(),
+                false => state.get_workflows()?.len(),
+            };
+            Ok((matches, total))
+        });
+        let (matches, total, error) = match found {
+            Ok((matches, total)) => (matches, total, None),
+            Err(error) => (vec![], 0, Some(error.to_string())),
+        };
+        Listing {
+            query: query.to_string(),
+            revision: Some(state.revision()),
+            matches,
+            total,
+            error,
+            diagnostics: state.diagnostics(),
+        }
+    }
+}
+
+/// Lists the workflows matching the query typed into the input line below
+/// the list, best match first
+pub struct CommandListComponent {
+    // the query
+    input: TextInput,
+    // selection and scroll offset, the offset is adjusted while rendering
+    list_state: RefCell<ListState>,
+    // the listed workflows and how many of them fit on a page, as seen on
+    // the last render
+    listing: RefCell<Listing>,
+    page_size: Cell<usize>,
+}
 
 impl CommandListComponent {
     pub fn new() -> Box<Self> {
-        Box::new(CommandListComponent {})
+        Box::new(CommandListComponent {
+            input: TextInput::new(),
+            list_state: RefCell::new(ListState::default().with_selected(Some(0))),
+            listing: RefCell::new(Listing::default()),
+            page_size: Cell::new(1),
+        })
+    }
+
+    /// Index of the selected workflow in the rendered list
+    pub fn selected(&self) -> Option<usize> {
+        self.list_state
+            .borrow()
+            .selected()
+            .filter(|selected| *selected < self.listing.borrow().matches.len())
+    }
+
+    /// A copy of the workflow at `index` of the rendered list
+    fn workflow(&self, index: usize) -> Box<Workflow> {
+        Box::new(self.listing.borrow().matches[index].workflow.clone())
+    }
+
+    /// Look the query up again if it or the workflows changed since the
+    /// last render
+    fn update_listing(&self, state: &AppState) {
+        let query = self.input.value();
+        let listing = self.listing.borrow();
+        if listing.query == query && listing.revision == Some(state.revision()) {
+            return;
+        }
+        drop(listing);
+        *self.listing.borrow_mut() = Listing::new(state, query);
+    }
+
+    /// Move the selection by `delta` rows, stopping at the first and last
+    /// workflow
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.listing.get_mut().matches.len().saturating_sub(1);
+        let selected = self.selected().unwrap_or(0);
+        let selected = selected.saturating_add_signed(delta).min(last);
+        self.list_state.get_mut().select(Some(selected));
     }
-}
 
+    /// Jump back to the best match after the query changed
+    fn reset_selection(&mut self) {
+        let list_state = self.list_state.get_mut();
+        list_state.select(Some(0));
+        *list_state.offset_mut() = 0;
+    }
+}
 
 impl Component for CommandListComponent {
-    fn render(&self, _state: &AppState, frame: &mut Frame, area: Rect) {
-        let area = frame.size();
-
-        // get list of all items from app state
-        let _commands = _state.get_workflows();
-
-        // create simple layout, having a single line at the end of the
-        // screen to input.
-        let layout
-            = Layout::default()
-            .constraints([
-                Constraint::Min(1),
-                Constraint::Max(3),
-            ].into_iter()).split(frame.size());
-
-        let main_screen = layout[0];
+    fn render(&self, state: &AppState, frame: &mut Frame, area: Rect) {
+        // the list and the preview of the selected workflow side by side
+        // take all space but a single line at the end of the screen to input.
+        let layout = Layout::default()
+            .constraints([Constraint::Min(1), Constraint::Max(3)])
+            .split(area);
         let input_line = layout[1];
+        let main_screen = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(layout[0]);
+        let list_area = main_screen[0];
+        let preview_area = main_screen[1];
 
-        let area = area.inner(&Margin {
-            vertical: 1,
-            horizontal: 2
-        });
+        self.update_listing(state);
+        let listing = self.listing.borrow();
+        let matches = &listing.matches;
+        let items: Vec<ListItem> = matches.iter().map(list_item).collect();
+        let len = items.len();
+        let item_height = items.first().map(ListItem::height).unwrap_or(1).max(1);
+        let list_height = list_area.height.saturating_sub(2) as usize;
+        self.page_size.set((list_height / item_height).max(1));
 
-        frame.render_widget(
-            Paragraph::new("Top")
-                .block(Block::new().borders(Borders::ALL)),
-            main_screen);
+        let mut list_state = self.list_state.borrow_mut();
+        // workflows may have been removed since the last render
+        let selected = list_state.selected().unwrap_or(0);
+        list_state.select(len.checked_sub(1).map(|last| selected.min(last)));
 
+        let list = List::new(items)
+            .block(Block::new().borders(Borders::ALL).title(" Workflows "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut list_state);
+
+        let mut scrollbar_state =
+            ScrollbarState::new(len).position(list_state.selected().unwrap_or(0));
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            list_area.inner(&Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut scrollbar_state,
+        );
+
+        let selected = list_state.selected().and_then(|i| matches.get(i));
         frame.render_widget(
-            Paragraph::new("Bottom")
-                .block(Block::new().borders(Borders::ALL)),
-            input_line);
+            WorkflowPreview::new(selected.map(|found| &found.workflow)),
+            preview_area,
+        );
 
-        // frame.render_widget(
-        //     List::new(_commands.unwrap()),
-        //     area,
-        // );
+        // let the user know about workflow files that could not be loaded
+        let mut input_block = Block::new()
+            .borders(Borders::ALL)
+            .title(Title::from(format!(" {}/{} ", len, listing.total)).alignment(Alignment::Right));
+        let diagnostics = &listing.diagnostics;
+        if let Some(message) = &state.message {
+            input_block = input_block.title(Span::styled(
+                format!(" {} ", message),
+                Style::default().fg(Color::Green),
+            ));
+        } else if let Some(error) = &listing.error {
+            input_block = input_block.title(Span::styled(
+                format!(" {} ", error),
+                Style::default().fg(Color::Red),
+            ));
+        } else if let Some(first) = diagnostics.first() {
+            input_block = input_block.title(Span::styled(
+                format!(
+                    " {} invalid workflow file(s), first: {} ",
+                    diagnostics.len(),
+                    first
+                ),
+                Style::default().fg(Color::Red),
+            ));
+        }
+
+        let width = input_line.width.saturating_sub(2 + PROMPT.len() as u16) as usize;
+        let (visible, cursor) = self.input.visible(width);
+        let prompt = Span::styled(PROMPT, Style::default().fg(Color::DarkGray));
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![prompt, Span::raw(visible)])).block(input_block),
+            input_line,
+        );
+        frame.set_cursor(
+            input_line.x + 1 + PROMPT.len() as u16 + cursor as u16,
+            input_line.y + 1,
+        );
     }
 
     fn handle_key_events(&mut self, key: KeyEvent) -> Action {
-        if key.kind == KeyEventKind::Press
-            && (key.code == KeyCode::Char('q') || key.code == KeyCode::Char('Q'))
-        {
-            println!("Quit Application");
-            Action::Quit
-        } else {
-            Action::None
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            let selected = self.selected().map(|i| self.workflow(i));
+            match (key.code, selected) {
+                (KeyCode::Char('c'), _) => return Action::Quit,
+                (KeyCode::Char('n'), _) => return Action::Edit(Box::default()),
+                (KeyCode::Char('o'), Some(workflow)) => return Action::Edit(workflow),
+                (KeyCode::Char('d'), Some(workflow)) => return Action::Duplicate(workflow),
+                _ => {}
+            }
+        }
+        let query = self.input.value().to_string();
+        if self.input.handle_key(key) {
+            if self.input.value() != query {
+                self.reset_selection();
+            }
+            return Action::None;
         }
+        let page = self.page_size.get() as isize;
+        match key.code {
+            // the first Esc clears the query, the next one quits
+            KeyCode::Esc if query.is_empty() => return Action::Quit,
+            KeyCode::Esc => {
+                self.input.clear();
+                self.reset_selection();
+            }
+            KeyCode::Enter => {
+                if let Some(selected) = self.selected() {
+                    return Action::Open(self.workflow(selected));
+                }
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-page),
+            KeyCode::PageDown => self.move_selection(page),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_paste_event(&mut self, text: String) -> Action {
+        self.input.insert_str(&text);
+        self.reset_selection();
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::composite::CompositeRepository;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::prelude::{WorkflowError, WorkflowResult};
+    use crate::ui::components::command_list::CommandListComponent;
+    use crate::ui::components::{Action, Component};
+    use crate::ui::AppState;
+    use crate::workflow::diagnostic::Diagnostic;
+    use crate::workflow::file_format::Workflow;
+    use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+    use crate::workflow::search::WorkflowMatch;
+    use crate::workflow::source::WorkflowId;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    /// A repository that can not be read
+    struct Unreadable;
+
+    impl WorkflowRepository for Unreadable {
+        fn id(&self) -> &str {
+            "unreadable"
+        }
+        fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+            Ok(ChangeSet::default())
+        }
+        fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+            Err(WorkflowError::NotFound(id.to_string()))
+        }
+        fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>> {
+            Err(WorkflowError::io(
+                std::path::Path::new("/workflows"),
+                "denied",
+            ))
+        }
+        fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<WorkflowId> {
+            Err(WorkflowError::NotFound(workflow.name))
+        }
+        fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+            Err(WorkflowError::NotFound(id.to_string()))
+        }
+        fn query_workflows(&self, _query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+            self.get_workflows().map(|_| vec![])
+        }
+        fn diagnostics(&self) -> Vec<Diagnostic> {
+            vec![]
+        }
+    }
+
+    fn press(component: &mut CommandListComponent, code: KeyCode) {
+        component.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_navigation_stays_within_the_list() {
+        let repository = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let state = AppState::new(Rc::new(RefCell::new(CompositeRepository::new(vec![
+            Box::new(repository),
+        ]))));
+        let mut component = CommandListComponent::new();
+        // 10 rows leave room for a single workflow of 4 lines per page
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.selected(), Some(0));
+
+        press(&mut component, KeyCode::Up);
+        assert_eq!(component.selected(), Some(0));
+        press(&mut component, KeyCode::Down);
+        assert_eq!(component.selected(), Some(1));
+        press(&mut component, KeyCode::End);
+        assert_eq!(component.selected(), Some(2));
+        press(&mut component, KeyCode::Down);
+        assert_eq!(component.selected(), Some(2));
+        press(&mut component, KeyCode::PageUp);
+        assert_eq!(component.selected(), Some(1));
+        press(&mut component, KeyCode::Home);
+        assert_eq!(component.selected(), Some(0));
+
+        // the selection is kept in view
+        press(&mut component, KeyCode::End);
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.list_state.borrow().offset(), 2);
+    }
+
+    #[test]
+    fn test_typing_filters_the_list() {
+        let repository = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let state = AppState::new(Rc::new(RefCell::new(CompositeRepository::new(vec![
+            Box::new(repository),
+        ]))));
+        let mut component = CommandListComponent::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        press(&mut component, KeyCode::Down);
+        for c in "tag:curl".chars() {
+            press(&mut component, KeyCode::Char(c));
+        }
+        assert_eq!(component.list_state.borrow().selected(), Some(0));
+
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.listing.borrow().matches.len(), 1);
+        let buffer = terminal.backend().buffer();
+        let row = |y| {
+            (0..60)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+        };
+        assert!(row(17).ends_with(" 1/3 ┐"));
+        assert!(row(18).starts_with("│> tag:curl "));
+        match component.handle_key_events(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)) {
+            Action::Open(workflow) => assert_eq!(workflow.tags, vec!["curl"]),
+            _ => panic!("Enter should open the selected workflow"),
+        }
+
+        assert!(matches!(
+            component.handle_key_events(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+            Action::None
+        ));
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.listing.borrow().matches.len(), 3);
+        assert!(matches!(
+            component.handle_key_events(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)),
+            Action::Quit
+        ));
+    }
+
+    #[test]
+    fn test_edit_and_duplicate_keys() {
+        let repository = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let state = AppState::new(Rc::new(RefCell::new(CompositeRepository::new(vec![
+            Box::new(repository),
+        ]))));
+        let mut component = CommandListComponent::new();
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        // nothing is selected before the list is rendered
+        assert_eq!(component.handle_key_events(ctrl('o')), Action::None);
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        let selected = *component.workflow(0);
+        assert_eq!(
+            component.handle_key_events(ctrl('o')),
+            Action::Edit(Box::new(selected.clone()))
+        );
+        assert_eq!(
+            component.handle_key_events(ctrl('d')),
+            Action::Duplicate(Box::new(selected))
+        );
+        assert_eq!(
+            component.handle_key_events(ctrl('n')),
+            Action::Edit(Box::default())
+        );
+    }
+
+    #[test]
+    fn test_list_follows_saved_workflows() {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = DirectoryRepository::new(dir.path().to_path_buf());
+        let mut state = AppState::new(Rc::new(RefCell::new(repository)));
+        let component = CommandListComponent::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.listing.borrow().matches.len(), 0);
+
+        state
+            .save_workflow(Workflow::new("Greet", "echo hello"))
+            .unwrap();
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        assert_eq!(component.listing.borrow().matches.len(), 1);
+        assert_eq!(component.workflow(0).name, "Greet");
+    }
+
+    #[test]
+    fn test_errors_are_shown_instead_of_an_empty_list() {
+        let state = AppState::new(Rc::new(RefCell::new(Unreadable)));
+        let component = CommandListComponent::new();
+        let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+        terminal
+            .draw(|frame| component.render(&state, frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let row: String = (0..60).map(|x| buffer.get(x, 17).symbol()).collect();
+        assert!(row.contains("denied"), "{}", row);
     }
 }
@@ -1,14 +1,33 @@
+pub mod argument_form;
 pub mod command_list;
+pub mod preview;
+pub mod text_area;
+pub mod text_input;
+pub mod workflow_editor;
 
 use crate::prelude::WorkflowResult;
 use crate::ui::AppState;
+use crate::workflow::file_format::Workflow;
 use crossterm::event::{Event, KeyEvent, MouseEvent};
 use ratatui::prelude::Rect;
 use ratatui::Frame;
 
+#[derive(Debug, PartialEq)]
 pub enum Action {
     None,
     Quit,
+    /// fill in the arguments of a workflow
+    Open(Box<Workflow>),
+    /// change a workflow, or write a new one if it has no source
+    Edit(Box<Workflow>),
+    /// save a changed workflow to its source, a new one to the write target
+    Save(Box<Workflow>),
+    /// copy a workflow to the write target, to change it there
+    Duplicate(Box<Workflow>),
+    /// return to the list
+    Back,
+    /// leave with this command
+    Select(String),
 }
 
 pub trait Component {
@@ -19,16 +38,19 @@ pub trait Component {
         match event {
             Some(Event::Key(key_event)) => self.handle_key_events(key_event),
             Some(Event::Mouse(mouse_event)) => self.handle_mouse_events(mouse_event),
+            Some(Event::Paste(text)) => self.handle_paste_event(text),
             _ => Action::None,
         }
     }
     fn handle_key_events(&mut self, _key: KeyEvent) -> Action {
-        println!("Handle Events {:?}", _key.state);
         Action::None
     }
     fn handle_mouse_events(&mut self, _mouse: MouseEvent) -> Action {
         Action::None
     }
+    fn handle_paste_event(&mut self, _text: String) -> Action {
+        Action::None
+    }
     fn update(&mut self, _state: &mut AppState, _action: Action) -> Action {
         Action::None
     }
@@ -0,0 +1,195 @@
+use crate::workflow::file_format::Workflow;
+use crate::workflow::template::{tokenize, Segment};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Widget, Wrap};
+
+/// Shows everything known about a workflow, with the placeholders of its
+/// command highlighted
+pub struct WorkflowPreview<'a> {
+    workflow: Option<&'a Workflow>,
+}
+
+impl<'a> WorkflowPreview<'a> {
+    pub fn new(workflow: Option<&'a Workflow>) -> Self {
+        WorkflowPreview { workflow }
+    }
+}
+
+fn label(name: &str) -> Span<'static> {
+    Span::styled(
+        format!("{}: ", name),
+        Style::default().add_modifier(Modifier::BOLD),
+    )
+}
+
+/// The lines of `command` with placeholders of known arguments in cyan and
+/// placeholders without an argument in red
+pub fn command_lines<'a>(workflow: &'a Workflow) -> Vec<Line<'a>> {
+    let known = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let unknown = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    let mut lines = vec![Line::default()];
+    for segment in tokenize(workflow.command.trim_end()) {
+        match segment {
+            Segment::Text(text) => {
+                for (i, part) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        lines.push(Line::default());
+                    }
+                    if !part.is_empty() {
+                        lines.last_mut().unwrap().spans.push(Span::raw(part));
+                    }
+                }
+            }
+            Segment::Placeholder(name) => {
+                let style = match workflow.arguments.iter().any(|a| a.name == name) {
+                    true => known,
+                    false => unknown,
+                };
+                let span = Span::styled(format!("{{{{{}}}}}", name), style);
+                lines.last_mut().unwrap().spans.push(span);
+            }
+        }
+    }
+    lines
+}
+
+fn details(workflow: &Workflow) -> Vec<Line<'_>> {
+    let mut lines = vec![Line::styled(
+        workflow.name.as_str(),
+        Style::default().add_modifier(Modifier::BOLD),
+    )];
+    if let Some(description) = &workflow.description {
+        lines.push(Line::raw(description.as_str()));
+    }
+    lines.push(Line::default());
+    lines.extend(command_lines(workflow));
+    lines.push(Line::default());
+
+    if !workflow.tags.is_empty() {
+        lines.push(Line::from(vec![
+            label("Tags"),
+            Span::raw(workflow.tags.join(", ")),
+        ]));
+    }
+    if !workflow.shells.is_empty() {
+        let shells: Vec<String> = workflow.shells.iter().map(|s| s.to_string()).collect();
+        lines.push(Line::from(vec![
+            label("Shells"),
+            Span::raw(shells.join(", ")),
+        ]));
+    }
+    if let Some(author) = workflow.author.as_ref().or(workflow.author_url.as_ref()) {
+        lines.push(Line::from(vec![
+            label("Author"),
+            Span::raw(author.as_str()),
+        ]));
+    }
+    if let Some(source_url) = &workflow.source_url {
+        lines.push(Line::from(vec![
+            label("Source"),
+            Span::raw(source_url.as_str()),
+        ]));
+    }
+    if let Some(id) = workflow.id() {
+        lines.push(Line::from(vec![label("Id"), Span::raw(id.to_string())]));
+    }
+    lines
+}
+
+fn arguments(workflow: &Workflow) -> Table<'_> {
+    let rows = workflow.arguments.iter().map(|argument| {
+        Row::new(vec![
+            Cell::from(argument.name.as_str()),
+            Cell::from(argument.description.as_deref().unwrap_or_default()),
+            Cell::from(argument.default_value.as_deref().unwrap_or_default()),
+        ])
+    });
+    Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(50),
+            Constraint::Percentage(25),
+        ],
+    )
+    .header(
+        Row::new(vec!["Argument", "Description", "Default"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::new().borders(Borders::TOP))
+}
+
+impl<'a> Widget for WorkflowPreview<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new().borders(Borders::ALL).title(" Preview ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let Some(workflow) = self.workflow else {
+            return;
+        };
+
+        // the argument table gets a row per argument below a header and
+        // a separator line
+        let table_height = match workflow.arguments.len() {
+            0 => 0,
+            n => n as u16 + 2,
+        };
+        let layout = Layout::default()
+            .constraints([Constraint::Min(1), Constraint::Length(table_height)])
+            .split(inner);
+        Paragraph::new(details(workflow))
+            .wrap(Wrap { trim: false })
+            .render(layout[0], buf);
+        if table_height > 0 {
+            Widget::render(arguments(workflow), layout[1], buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::components::preview::{command_lines, WorkflowPreview};
+    use crate::workflow::file_format::{Argument, Workflow};
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::Color;
+    use ratatui::widgets::Widget;
+
+    fn workflow() -> Workflow {
+        Workflow::new("Undo", "git reset HEAD~{{count}}\ngit status {{path}}")
+            .with_arguments(vec![Argument::new("count").with_description("commits")])
+    }
+
+    #[test]
+    fn test_command_placeholders_are_highlighted() {
+        let workflow = workflow();
+        let lines = command_lines(&workflow);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].spans[1].content, "{{count}}");
+        assert_eq!(lines[0].spans[1].style.fg, Some(Color::Cyan));
+        assert_eq!(lines[1].spans[1].content, "{{path}}");
+        assert_eq!(lines[1].spans[1].style.fg, Some(Color::Red));
+    }
+
+    #[test]
+    fn test_render_preview() {
+        let workflow = workflow();
+        let area = Rect::new(0, 0, 40, 12);
+        let mut buffer = Buffer::empty(area);
+        WorkflowPreview::new(Some(&workflow)).render(area, &mut buffer);
+        let row = |y| {
+            (0..40)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+        };
+        assert!(row(1).starts_with("│Undo "));
+        assert!(row(3).starts_with("│git reset HEAD~{{count}} "));
+        assert!(row(9).starts_with("│Argument"));
+        assert!(row(10).starts_with("│count      commits "));
+    }
+}
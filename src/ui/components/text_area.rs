@@ -0,0 +1,164 @@
+use crate::ui::components::text_input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// Several lines of editable text, each line a [`TextInput`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextArea {
+    lines: Vec<TextInput>,
+    // index of the line with the cursor
+    row: usize,
+}
+
+impl TextArea {
+    /// Start with `text`, the cursor at its end
+    pub fn new(text: &str) -> Self {
+        let mut area = TextArea {
+            lines: vec![TextInput::new()],
+            row: 0,
+        };
+        area.insert_str(text);
+        area
+    }
+
+    /// The lines joined by line breaks
+    pub fn value(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(TextInput::value).collect();
+        lines.join("\n")
+    }
+
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Insert `text` at the cursor, line breaks start new lines
+    pub fn insert_str(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.new_line();
+            }
+            self.lines[self.row].insert_str(line.trim_end_matches('\r'));
+        }
+    }
+
+    /// Break the line at the cursor
+    fn new_line(&mut self) {
+        let rest = self.lines[self.row].split_off();
+        let mut line = TextInput::new();
+        line.insert_str(&rest);
+        line.set_cursor(0);
+        self.row += 1;
+        self.lines.insert(self.row, line);
+    }
+
+    /// Append the line `row` to the one before it, the cursor ends up
+    /// where they were joined
+    fn join(&mut self, row: usize) {
+        let line = self.lines.remove(row);
+        self.row = row - 1;
+        let previous = &mut self.lines[self.row];
+        previous.set_cursor(usize::MAX);
+        let cursor = previous.cursor();
+        previous.insert_str(line.value());
+        previous.set_cursor(cursor);
+    }
+
+    /// Move the cursor to `row`, keeping its column if the line is long
+    /// enough
+    fn move_to(&mut self, row: usize) {
+        let cursor = self.lines[self.row].cursor();
+        self.row = row;
+        self.lines[self.row].set_cursor(cursor);
+    }
+
+    /// Apply an editing key. Returns whether the key was used, `Up` and
+    /// `Down` are not on the first and last line.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let line = &self.lines[self.row];
+        let at_start = line.cursor() == 0;
+        let at_end = line.cursor() == line.value().chars().count();
+        match key.code {
+            KeyCode::Enter => self.new_line(),
+            KeyCode::Backspace if at_start && self.row > 0 => self.join(self.row),
+            KeyCode::Delete if at_end && self.row + 1 < self.lines.len() => self.join(self.row + 1),
+            KeyCode::Up if self.row > 0 => self.move_to(self.row - 1),
+            KeyCode::Down if self.row + 1 < self.lines.len() => self.move_to(self.row + 1),
+            _ => return self.lines[self.row].handle_key(key),
+        }
+        true
+    }
+
+    /// The lines that fit into `width` columns and `height` rows with the
+    /// cursor in view, and the column and row of the cursor within them
+    pub fn visible(&self, width: usize, height: usize) -> (Vec<String>, (usize, usize)) {
+        let scroll = self.row.saturating_sub(height.saturating_sub(1));
+        let mut column = 0;
+        let lines = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(height)
+            .map(|(i, line)| match i == self.row {
+                true => {
+                    let (visible, cursor) = line.visible(width);
+                    column = cursor;
+                    visible
+                }
+                false => line.value().chars().take(width).collect(),
+            })
+            .collect();
+        (lines, (column, self.row - scroll))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::components::text_area::TextArea;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn press(area: &mut TextArea, code: KeyCode) -> bool {
+        area.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_breaking_and_joining_lines() {
+        let mut area = TextArea::new("curl {{url}}\r\ndemo\n");
+        assert_eq!(area.height(), 3);
+        assert_eq!(area.value(), "curl {{url}}\ndemo\n");
+
+        press(&mut area, KeyCode::Backspace);
+        assert_eq!(area.value(), "curl {{url}}\ndemo");
+        press(&mut area, KeyCode::Left);
+        press(&mut area, KeyCode::Left);
+        press(&mut area, KeyCode::Enter);
+        assert_eq!(area.value(), "curl {{url}}\nde\nmo");
+        press(&mut area, KeyCode::Backspace);
+        assert_eq!(area.value(), "curl {{url}}\ndemo");
+        press(&mut area, KeyCode::Up);
+        area.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL));
+        press(&mut area, KeyCode::Delete);
+        assert_eq!(area.value(), "curl {{url}}demo");
+    }
+
+    #[test]
+    fn test_up_and_down_keep_the_column() {
+        let mut area = TextArea::new("git add .\ngit commit");
+        press(&mut area, KeyCode::Left);
+        assert!(press(&mut area, KeyCode::Up));
+        press(&mut area, KeyCode::Char('!'));
+        assert_eq!(area.value(), "git add .!\ngit commit");
+        // leaving the area is up to whoever holds it
+        assert!(!press(&mut area, KeyCode::Up));
+        assert!(press(&mut area, KeyCode::Down));
+        assert!(!press(&mut area, KeyCode::Down));
+    }
+
+    #[test]
+    fn test_visible_keeps_the_cursor_in_view() {
+        let area = TextArea::new("a\nb\nc\nlonger");
+        assert_eq!(
+            area.visible(4, 2),
+            (vec!["c".to_string(), "ger".to_string()], (3, 1))
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// A single line of editable text with a cursor, using readline like keys
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    // position of the cursor in chars
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        TextInput::default()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Position of the cursor in chars
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Move the cursor to `cursor`, or to the end if the value is shorter
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.len());
+    }
+
+    /// Remove the text after the cursor and return it
+    pub fn split_off(&mut self) -> String {
+        let index = self.byte_index(self.cursor);
+        self.value.split_off(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index(self.cursor);
+        self.value.insert(index, c);
+        self.cursor += 1;
+    }
+
+    /// Insert pasted text, line breaks become spaces
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.insert(if c == '\n' || c == '\r' { ' ' } else { c });
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.value.remove(self.byte_index(self.cursor));
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            self.value.remove(self.byte_index(self.cursor));
+        }
+    }
+
+    /// Delete the word before the cursor and the whitespace following it
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.value.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    pub fn delete_to_start(&mut self) {
+        let range = 0..self.byte_index(self.cursor);
+        self.value.replace_range(range, "");
+        self.cursor = 0;
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    /// The part of the value that fits into `width` columns with the
+    /// cursor in view, and the column of the cursor within it
+    pub fn visible(&self, width: usize) -> (String, usize) {
+        let scroll = self.cursor.saturating_sub(width.saturating_sub(1));
+        let visible = self.value.chars().skip(scroll).take(width).collect();
+        (visible, self.cursor - scroll)
+    }
+
+    /// Apply an editing key. Returns whether the key was used.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('u') if ctrl => self.delete_to_start(),
+            KeyCode::Char(_) if ctrl || alt => return false,
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Backspace if alt || ctrl => self.delete_word(),
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.move_left(),
+            KeyCode::Right => self.move_right(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::components::text_input::TextInput;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::new();
+        input.insert_str(text);
+        input
+    }
+
+    #[test]
+    fn test_editing_at_the_cursor() {
+        let mut input = typed("gt");
+        input.move_left();
+        input.insert('i');
+        assert_eq!(input.value(), "git");
+        assert_eq!(input.cursor(), 2);
+
+        input.delete();
+        input.backspace();
+        assert_eq!(input.value(), "g");
+        input.move_left();
+        input.move_left();
+        input.backspace();
+        assert_eq!((input.value(), input.cursor()), ("g", 0));
+
+        let mut input = typed("ümlaut\nline");
+        assert_eq!(input.value(), "ümlaut line");
+        input.move_left();
+        input.move_right();
+        input.move_right();
+        assert_eq!(input.cursor(), 11);
+    }
+
+    #[test]
+    fn test_word_delete() {
+        let mut input = typed("tag:git undo  ");
+        input.delete_word();
+        assert_eq!(input.value(), "tag:git ");
+        input.delete_word();
+        assert_eq!(input.value(), "");
+
+        let mut input = typed("tag:git undo");
+        input.move_left();
+        input.move_left();
+        input.delete_to_start();
+        assert_eq!((input.value(), input.cursor()), ("do", 0));
+    }
+
+    #[test]
+    fn test_visible_keeps_the_cursor_in_view() {
+        let mut input = typed("0123456789");
+        assert_eq!(input.visible(4), ("789".to_string(), 3));
+        input.cursor = 2;
+        assert_eq!(input.visible(4), ("0123".to_string(), 2));
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut input = typed("git");
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert!(input.handle_key(ctrl('a')));
+        assert!(input.handle_key(KeyEvent::new(KeyCode::Char('.'), KeyModifiers::NONE)));
+        assert_eq!(input.value(), ".git");
+        assert!(input.handle_key(ctrl('w')));
+        assert_eq!(input.value(), "git");
+        assert!(input.handle_key(ctrl('e')));
+        assert!(input.handle_key(ctrl('w')));
+        assert_eq!(input.value(), "");
+        assert!(!input.handle_key(ctrl('c')));
+        assert!(!input.handle_key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)));
+    }
+}
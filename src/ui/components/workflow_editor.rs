@@ -0,0 +1,497 @@
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::ui::components::text_area::TextArea;
+use crate::ui::components::text_input::TextInput;
+use crate::ui::components::{Action, Component};
+use crate::ui::AppState;
+use crate::workflow::file_format::{Argument, Workflow};
+use crate::workflow::template;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Position, Title};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+// rows of a single line input, including its border
+const FIELD_HEIGHT: u16 = 3;
+// lines of the command shown at most, longer commands scroll
+const COMMAND_LINES: u16 = 6;
+// names of the inputs of an argument row, shown while they are empty
+const ARGUMENT_COLUMNS: [&str; 3] = ["name", "description", "default value"];
+
+/// A part of the editor that takes input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Name,
+    Description,
+    Tags,
+    Command,
+    /// a column of a row of the argument list
+    Argument(usize, usize),
+}
+
+/// An argument in the argument list
+struct ArgumentRow {
+    // keeps what there are no inputs for, like the type of the argument
+    argument: Argument,
+    // name, description and default value
+    inputs: [TextInput; 3],
+}
+
+impl ArgumentRow {
+    fn new(argument: Argument) -> Self {
+        let values = [
+            Some(argument.name.as_str()),
+            argument.description.as_deref(),
+            argument.default_value.as_deref(),
+        ];
+        let inputs = values.map(|value| {
+            let mut input = TextInput::new();
+            input.insert_str(value.unwrap_or_default());
+            input
+        });
+        ArgumentRow { argument, inputs }
+    }
+
+    fn argument(&self) -> Argument {
+        let [name, description, default_value] = &self.inputs;
+        Argument {
+            name: name.value().trim().to_string(),
+            description: Some(description.value().trim().to_string()).filter(|d| !d.is_empty()),
+            default_value: Some(default_value.value().to_string()).filter(|d| !d.is_empty()),
+            ..self.argument.clone()
+        }
+    }
+}
+
+/// Edits the name, description, tags, command and arguments of a workflow,
+/// or writes a new one, and saves it with Ctrl-S
+pub struct WorkflowEditorComponent {
+    // the workflow as it was opened, keeps what there are no inputs for
+    workflow: Workflow,
+    name: TextInput,
+    description: TextInput,
+    // comma separated
+    tags: TextInput,
+    command: TextArea,
+    arguments: Vec<ArgumentRow>,
+    focused: Field,
+}
+
+fn input_with(value: &str) -> TextInput {
+    let mut input = TextInput::new();
+    input.insert_str(value);
+    input
+}
+
+impl WorkflowEditorComponent {
+    pub fn new(workflow: Workflow) -> Box<Self> {
+        Box::new(WorkflowEditorComponent {
+            name: input_with(&workflow.name),
+            description: input_with(workflow.description.as_deref().unwrap_or_default()),
+            tags: input_with(&workflow.tags.join(", ")),
+            command: TextArea::new(&workflow.command),
+            arguments: workflow
+                .arguments
+                .iter()
+                .cloned()
+                .map(ArgumentRow::new)
+                .collect(),
+            focused: Field::Name,
+            workflow,
+        })
+    }
+
+    /// The workflow with the values of the inputs
+    pub fn workflow(&self) -> Workflow {
+        let description = self.description.value().trim();
+        let tags = self.tags.value().split(',').map(str::trim);
+        Workflow {
+            name: self.name.value().trim().to_string(),
+            description: Some(description.to_string()).filter(|d| !d.is_empty()),
+            tags: tags.filter(|t| !t.is_empty()).map(String::from).collect(),
+            command: self.command.value(),
+            arguments: self.arguments.iter().map(ArgumentRow::argument).collect(),
+            ..self.workflow.clone()
+        }
+    }
+
+    /// The workflow with the values of the inputs, if it can be saved
+    pub fn checked(&self) -> WorkflowResult<Workflow> {
+        let workflow = self.workflow();
+        let invalid = |message: &str| Err(WorkflowError::InvalidArgument(message.to_string()));
+        if workflow.name.is_empty() {
+            return invalid("the workflow needs a name");
+        }
+        if workflow.arguments.iter().any(|a| a.name.is_empty()) {
+            return invalid("every argument needs a name");
+        }
+        workflow.check()?;
+        Ok(workflow)
+    }
+
+    /// All fields in the order Tab moves through them
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = vec![Field::Name, Field::Description, Field::Tags, Field::Command];
+        for row in 0..self.arguments.len() {
+            fields.extend((0..ARGUMENT_COLUMNS.len()).map(|column| Field::Argument(row, column)));
+        }
+        fields
+    }
+
+    fn move_focus(&mut self, delta: isize) {
+        let fields = self.fields();
+        let index = fields.iter().position(|f| *f == self.focused).unwrap_or(0);
+        let index = (index as isize + delta).rem_euclid(fields.len() as isize);
+        self.focused = fields[index as usize];
+    }
+
+    fn input_mut(&mut self, field: Field) -> Option<&mut TextInput> {
+        match field {
+            Field::Name => Some(&mut self.name),
+            Field::Description => Some(&mut self.description),
+            Field::Tags => Some(&mut self.tags),
+            Field::Command => None,
+            Field::Argument(row, column) => {
+                self.arguments.get_mut(row).map(|r| &mut r.inputs[column])
+            }
+        }
+    }
+
+    /// Add an argument for the first placeholder without one, or an
+    /// unnamed argument if there is none
+    fn add_argument(&mut self) {
+        let command = self.command.value();
+        let segments = template::tokenize(&command);
+        let name = template::placeholders(&segments)
+            .into_iter()
+            .find(|name| {
+                !self
+                    .arguments
+                    .iter()
+                    .any(|row| row.inputs[0].value() == *name)
+            })
+            .unwrap_or_default();
+        self.arguments.push(ArgumentRow::new(Argument::new(name)));
+        let column = if name.is_empty() { 0 } else { 1 };
+        self.focused = Field::Argument(self.arguments.len() - 1, column);
+    }
+
+    fn remove_argument(&mut self) {
+        if let Field::Argument(row, _) = self.focused {
+            self.arguments.remove(row);
+            self.focused = match self.arguments.len() {
+                0 => Field::Command,
+                len => Field::Argument(row.min(len - 1), 0),
+            };
+        }
+    }
+
+    fn border_style(&self, field: Field) -> Style {
+        match self.focused == field {
+            true => Style::default().fg(Color::Yellow),
+            false => Style::default(),
+        }
+    }
+
+    fn render_input(&self, frame: &mut Frame, area: Rect, field: Field, title: &str) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_style(self.border_style(field))
+            .title(format!(" {} ", title));
+        let input = match field {
+            Field::Name => &self.name,
+            Field::Description => &self.description,
+            _ => &self.tags,
+        };
+        let (visible, cursor) = input.visible(area.width.saturating_sub(2) as usize);
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+        if self.focused == field {
+            frame.set_cursor(area.x + 1 + cursor as u16, area.y + 1);
+        }
+    }
+
+    fn render_command(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_style(self.border_style(Field::Command))
+            .title(" Command ");
+        let inner = block.inner(area);
+        let (lines, (column, row)) = self
+            .command
+            .visible(inner.width as usize, inner.height as usize);
+        let lines: Vec<Line> = lines.into_iter().map(Line::raw).collect();
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+        if self.focused == Field::Command {
+            frame.set_cursor(inner.x + column as u16, inner.y + row as u16);
+        }
+    }
+
+    fn render_arguments(&self, frame: &mut Frame, area: Rect) {
+        let focused = matches!(self.focused, Field::Argument(..));
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_style(match focused {
+                true => Style::default().fg(Color::Yellow),
+                false => Style::default(),
+            })
+            .title(" Arguments ")
+            .title(
+                Title::from(" Ctrl-N: add, Ctrl-R: remove ")
+                    .position(Position::Top)
+                    .alignment(Alignment::Right),
+            );
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+        for (i, row) in self
+            .arguments
+            .iter()
+            .enumerate()
+            .take(inner.height as usize)
+        {
+            let line = Rect::new(inner.x, inner.y + i as u16, inner.width, 1);
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(45),
+                    Constraint::Percentage(30),
+                ])
+                .split(line);
+            for (column, input) in row.inputs.iter().enumerate() {
+                let cell = columns[column];
+                let width = cell.width.saturating_sub(1) as usize;
+                let (visible, cursor) = input.visible(width);
+                let span = match (
+                    self.focused == Field::Argument(i, column),
+                    visible.is_empty(),
+                ) {
+                    (true, _) => {
+                        frame.set_cursor(cell.x + cursor as u16, cell.y);
+                        Span::styled(visible, Style::default().fg(Color::Yellow))
+                    }
+                    (false, true) => Span::styled(
+                        ARGUMENT_COLUMNS[column],
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    (false, false) => Span::raw(visible),
+                };
+                frame.render_widget(Paragraph::new(span), cell);
+            }
+        }
+    }
+}
+
+/// Center the editor in `area`, as high as its fields need if there is room
+fn popup_area(area: Rect, height: u16) -> Rect {
+    let width = area.width * 4 / 5;
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+impl Component for WorkflowEditorComponent {
+    fn render(&self, state: &AppState, frame: &mut Frame, area: Rect) {
+        let command_height = (self.command.height() as u16).clamp(1, COMMAND_LINES) + 2;
+        let arguments_height = self.arguments.len().max(1) as u16 + 2;
+        let area = popup_area(
+            area,
+            3 * FIELD_HEIGHT + command_height + arguments_height + 3,
+        );
+
+        let title = match self.workflow.id() {
+            Some(id) => format!(" Edit {} ", id),
+            None => " New workflow ".to_string(),
+        };
+        let block = Block::new().borders(Borders::ALL).title(title).title(
+            Title::from(" Tab: next field, Ctrl-S: save, Esc: back ").position(Position::Bottom),
+        );
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::default()
+            .constraints([
+                Constraint::Length(FIELD_HEIGHT),
+                Constraint::Length(FIELD_HEIGHT),
+                Constraint::Length(FIELD_HEIGHT),
+                Constraint::Length(command_height),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+        self.render_input(frame, layout[0], Field::Name, "Name");
+        self.render_input(frame, layout[1], Field::Description, "Description");
+        self.render_input(frame, layout[2], Field::Tags, "Tags, comma separated");
+        self.render_command(frame, layout[3]);
+        self.render_arguments(frame, layout[4]);
+
+        // what keeps the workflow from being saved, or why saving failed
+        let problem = match (self.checked(), &state.message) {
+            (Err(error), _) => Some(error.to_string()),
+            (Ok(_), message) => message.clone(),
+        };
+        if let Some(problem) = problem {
+            let style = Style::default().fg(Color::Red);
+            frame.render_widget(Paragraph::new(problem).style(style), layout[5]);
+        }
+    }
+
+    fn handle_key_events(&mut self, key: KeyEvent) -> Action {
+        if key.kind != KeyEventKind::Press {
+            return Action::None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Action::Quit,
+            KeyCode::Char('s') if ctrl => {
+                return match self.checked() {
+                    Ok(workflow) => Action::Save(Box::new(workflow)),
+                    Err(_) => Action::None,
+                }
+            }
+            KeyCode::Char('n') if ctrl => self.add_argument(),
+            KeyCode::Char('r') if ctrl => self.remove_argument(),
+            KeyCode::Esc => return Action::Back,
+            KeyCode::Tab => self.move_focus(1),
+            KeyCode::BackTab => self.move_focus(-1),
+            _ if self.focused == Field::Command => {
+                if !self.command.handle_key(key) {
+                    match key.code {
+                        KeyCode::Up => self.move_focus(-1),
+                        KeyCode::Down => self.move_focus(1),
+                        _ => {}
+                    }
+                }
+            }
+            KeyCode::Enter | KeyCode::Down => self.move_focus(1),
+            KeyCode::Up => self.move_focus(-1),
+            _ => {
+                if let Some(input) = self.input_mut(self.focused) {
+                    input.handle_key(key);
+                }
+            }
+        }
+        Action::None
+    }
+
+    fn handle_paste_event(&mut self, text: String) -> Action {
+        match self.input_mut(self.focused) {
+            Some(input) => input.insert_str(&text),
+            None => self.command.insert_str(&text),
+        }
+        Action::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::composite::CompositeRepository;
+    use crate::ui::components::workflow_editor::{Field, WorkflowEditorComponent};
+    use crate::ui::components::{Action, Component};
+    use crate::ui::AppState;
+    use crate::workflow::file_format::{Argument, ArgumentType, Workflow};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn press(editor: &mut WorkflowEditorComponent, code: KeyCode) -> Action {
+        editor.handle_key_events(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn ctrl(editor: &mut WorkflowEditorComponent, c: char) -> Action {
+        editor.handle_key_events(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    fn write(editor: &mut WorkflowEditorComponent, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => press(editor, KeyCode::Enter),
+                c => press(editor, KeyCode::Char(c)),
+            };
+        }
+    }
+
+    #[test]
+    fn test_write_a_new_workflow() {
+        let mut editor = WorkflowEditorComponent::new(Workflow::default());
+        assert_eq!(ctrl(&mut editor, 's'), Action::None);
+
+        write(&mut editor, "Undo\n\ngit, reset\n");
+        assert_eq!(editor.focused, Field::Command);
+        write(&mut editor, "git reset \\\n  HEAD~{{count}}");
+        assert!(editor.checked().is_err());
+
+        ctrl(&mut editor, 'n');
+        assert_eq!(editor.focused, Field::Argument(0, 1));
+        write(&mut editor, "how many\n1");
+
+        let Action::Save(workflow) = ctrl(&mut editor, 's') else {
+            panic!("the workflow is complete");
+        };
+        assert_eq!(workflow.name, "Undo");
+        assert_eq!(workflow.description, None);
+        assert_eq!(workflow.tags, vec!["git", "reset"]);
+        assert_eq!(workflow.command, "git reset \\\n  HEAD~{{count}}");
+        assert_eq!(workflow.arguments[0].name, "count");
+        assert_eq!(
+            workflow.arguments[0].description.as_deref(),
+            Some("how many")
+        );
+        assert_eq!(workflow.arguments[0].default_value.as_deref(), Some("1"));
+        assert_eq!(press(&mut editor, KeyCode::Esc), Action::Back);
+    }
+
+    #[test]
+    fn test_edit_keeps_what_has_no_input() {
+        let mut count = Argument::new("count").with_type(ArgumentType::Int);
+        count.min = Some(1);
+        let workflow = Workflow::new("Undo", "git reset HEAD~{{count}} {{mode}}")
+            .with_arguments(vec![count, Argument::new("mode")]);
+        let mut editor = WorkflowEditorComponent::new(workflow.clone());
+        assert_eq!(editor.workflow(), workflow);
+
+        // remove mode, from the command and from the arguments
+        press(&mut editor, KeyCode::BackTab);
+        ctrl(&mut editor, 'r');
+        assert_eq!(editor.focused, Field::Argument(0, 0));
+        press(&mut editor, KeyCode::BackTab);
+        assert_eq!(editor.focused, Field::Command);
+        for _ in 0.."{{mode}} ".len() {
+            press(&mut editor, KeyCode::Backspace);
+        }
+
+        let Action::Save(edited) = ctrl(&mut editor, 's') else {
+            panic!("the workflow is complete");
+        };
+        assert_eq!(edited.command, "git reset HEAD~{{count}}");
+        assert_eq!(edited.arguments.len(), 1);
+        assert_eq!(edited.arguments[0].kind, ArgumentType::Int);
+        assert_eq!(edited.arguments[0].min, Some(1));
+    }
+
+    #[test]
+    fn test_render_shows_what_is_missing() {
+        let state = AppState::new(Rc::new(RefCell::new(CompositeRepository::default())));
+        let editor = WorkflowEditorComponent::new(Workflow::new("Greet", "echo {{name}}"));
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal
+            .draw(|frame| editor.render(&state, frame, frame.size()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = (0..24)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .map(|(x, y)| buffer.get(x, y).symbol())
+            .collect();
+        assert!(screen.contains(" New workflow "));
+        assert!(screen.contains("echo {{name}}"));
+        assert!(screen.contains("unknown placeholder(s) {{name}}"));
+    }
+}
@@ -1,83 +1,241 @@
-use std::path::PathBuf;
-
-use crate::prelude::directory::DirectoryRepository;
-use crate::prelude::git::GitRepository;
-use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::prelude::WorkflowResult;
+use crate::ui::components::argument_form::ArgumentFormComponent;
 use crate::ui::components::command_list::CommandListComponent;
-use crate::ui::components::{Component};
-use crate::workflow::file_format::Workflow;
-use crate::workflow::repository::WorkflowRepository;
+use crate::ui::components::workflow_editor::WorkflowEditorComponent;
+use crate::ui::components::{Action, Component};
+use crate::workflow::diagnostic::Diagnostic;
+use crate::workflow::file_format::{Shell, Workflow};
+use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+use crate::workflow::search::WorkflowMatch;
+use crate::workflow::source::WorkflowId;
+use di::RefMut;
+use std::collections::HashMap;
 
 pub mod components;
 
+#[derive(Debug, PartialEq)]
 pub enum Focus {
     List,
+    Form,
+    Editor,
 }
 
 pub struct AppState {
-    pub git_repo: GitRepository,
-    pub dir_repo: DirectoryRepository,
+    // all configured workflow sources, shared with the service provider
+    pub repository: RefMut<dyn WorkflowRepository>,
+    // id() hands out a reference, which a borrow of repository can not
+    id: String,
+    // counts the changes made to the workflows through this state
+    revision: usize,
     pub focus: Focus,
 
     // components to be rendered
     pub command_list_component: Box<dyn Component>,
+    // the arguments of the chosen workflow, while they are filled in
+    pub argument_form_component: Option<Box<dyn Component>>,
+    // the workflow being written or changed
+    pub workflow_editor_component: Option<Box<dyn Component>>,
+    // the outcome of the last save, until the next event
+    pub message: Option<String>,
+    // the shell the selected command is inserted into, if known
+    pub shell: Option<Shell>,
 }
 
 impl AppState {
-    pub fn new(url: &str, branch: &str, repo_dir: PathBuf, dir_path: PathBuf) -> Box<Self> {
+    pub fn new(repository: RefMut<dyn WorkflowRepository>) -> Box<Self> {
+        let id = repository.borrow().id().to_string();
         Box::new(AppState {
-            git_repo: GitRepository::new(url, branch, repo_dir),
-            dir_repo: DirectoryRepository::new(dir_path),
+            repository,
+            id,
+            revision: 0,
             command_list_component: CommandListComponent::new(),
+            argument_form_component: None,
+            workflow_editor_component: None,
+            message: None,
+            shell: None,
             focus: Focus::List,
         })
     }
 
+    /// Switch between the list, the argument form and the editor, and save
+    /// workflows. Actions that end the application are returned to the
+    /// caller.
+    pub fn apply(&mut self, action: Action) -> Action {
+        self.message = None;
+        match action {
+            Action::Open(workflow) => {
+                // there is nothing to fill in, unless the command is broken
+                // and the form has to show why
+                if workflow.arguments.is_empty() {
+                    if let Ok(command) = workflow.render(HashMap::new()) {
+                        return Action::Select(command);
+                    }
+                }
+                self.argument_form_component =
+                    Some(ArgumentFormComponent::new(*workflow, self.shell.clone()));
+                self.focus = Focus::Form;
+                Action::None
+            }
+            Action::Edit(workflow) => {
+                self.workflow_editor_component = Some(WorkflowEditorComponent::new(*workflow));
+                self.focus = Focus::Editor;
+                Action::None
+            }
+            // the editor stays open with the error if saving fails
+            Action::Save(workflow) => {
+                match self.save_workflow(*workflow) {
+                    Ok(id) => {
+                        self.message = Some(format!("saved {}", id));
+                        self.apply_back();
+                    }
+                    Err(error) => self.message = Some(error.to_string()),
+                }
+                Action::None
+            }
+            // a copy without a source is saved to a new file
+            Action::Duplicate(workflow) => {
+                let copy = Workflow {
+                    source: None,
+                    ..*workflow
+                };
+                match self
+                    .save_workflow(copy)
+                    .and_then(|id| self.get_workflow(&id))
+                {
+                    Ok(copy) => return self.apply(Action::Edit(Box::new(copy))),
+                    Err(error) => self.message = Some(error.to_string()),
+                }
+                Action::None
+            }
+            Action::Back => {
+                self.apply_back();
+                Action::None
+            }
+            action => action,
+        }
+    }
+
+    /// Changes whenever workflows are saved, deleted or refreshed, so
+    /// components know when to look at them again
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    fn apply_back(&mut self) {
+        self.argument_form_component = None;
+        self.workflow_editor_component = None;
+        self.focus = Focus::List;
+    }
 }
 
 impl WorkflowRepository for AppState {
-    fn refresh(&mut self) -> WorkflowResult<()> {
-        let _ = self.git_repo.refresh();
-        self.dir_repo.refresh()
+    fn id(&self) -> &str {
+        &self.id
+    }
 
+    fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+        self.revision += 1;
+        self.repository.borrow_mut().refresh()
     }
 
-    fn get_workflow(&self, name: &str) -> WorkflowResult<Workflow> {
-        match self.dir_repo.get_workflow(name) {
-            Ok(wkf) => { Ok(wkf) }
-            Err(_) => {
-                match self.git_repo.get_workflow(name) {
-                    Ok(wkf) => { Ok(wkf) }
-                    Err(_) => {Err(WorkflowError::NotFound(format!("{} not found", name)))}
-                }
-            }
-        }
+    fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+        self.repository.borrow().get_workflow(id)
     }
 
     fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>> {
-        let mut all = self.dir_repo.get_workflows()?;
-        let mut all_git = self.git_repo.get_workflows()?;
-        all.append(&mut all_git);
-        Ok(all)
+        self.repository.borrow().get_workflows()
     }
 
-    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<()> {
-        // only save to local. making copies of Workflows
-        self.dir_repo.save_workflow(workflow)
+    fn is_writable(&self) -> bool {
+        self.repository.borrow().is_writable()
     }
 
-    fn delete_workflow(&mut self, name: &str) -> WorkflowResult<()> {
-        self.dir_repo.delete_workflow(name)
+    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<WorkflowId> {
+        self.revision += 1;
+        self.repository.borrow_mut().save_workflow(workflow)
     }
 
-    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<Workflow>> {
-        let mut result = vec![];
-        if let Ok(mut all_local) = self.dir_repo.query_workflows(query) {
-            result.append(&mut all_local);
-        }
-        if let Ok(mut all_git) = self.git_repo.query_workflows(query) {
-            result.append(&mut all_git);
-        }
-        Ok(result)
+    fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+        self.revision += 1;
+        self.repository.borrow_mut().delete_workflow(id)
+    }
+
+    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+        self.repository.borrow().query_workflows(query)
+    }
+
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.repository.borrow().diagnostics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::composite::CompositeRepository;
+    use crate::prelude::directory::DirectoryRepository;
+    use crate::prelude::repository::WorkflowRepository;
+    use crate::ui::components::Action;
+    use crate::ui::{AppState, Focus};
+    use crate::workflow::file_format::{Argument, Workflow};
+    use crate::workflow::source::WorkflowId;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_open_and_close_the_argument_form() {
+        let mut state = AppState::new(Rc::new(RefCell::new(CompositeRepository::default())));
+        let workflow =
+            Workflow::new("Greet", "echo {{name}}").with_arguments(vec![Argument::new("name")]);
+
+        assert_eq!(state.apply(Action::Open(Box::new(workflow))), Action::None);
+        assert_eq!(state.focus, Focus::Form);
+        assert!(state.argument_form_component.is_some());
+
+        assert_eq!(state.apply(Action::Back), Action::None);
+        assert_eq!(state.focus, Focus::List);
+        assert!(state.argument_form_component.is_none());
+
+        let workflow = Workflow::new("Status", "git status");
+        assert_eq!(
+            state.apply(Action::Open(Box::new(workflow))),
+            Action::Select("git status".to_string())
+        );
+    }
+
+    #[test]
+    fn test_duplicate_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = DirectoryRepository::new(dir.path().to_path_buf());
+        let team =
+            DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows")).with_id("team");
+        let repository = CompositeRepository::new(vec![Box::new(team), Box::new(local)])
+            .with_write_target("local");
+        let mut state = AppState::new(Rc::new(RefCell::new(repository)));
+        let workflow = state
+            .get_workflow(&WorkflowId::new("team", "sample.yaml"))
+            .unwrap();
+
+        // the copy is saved right away and opened to be changed
+        assert_eq!(
+            state.apply(Action::Duplicate(Box::new(workflow.clone()))),
+            Action::None
+        );
+        assert_eq!(state.focus, Focus::Editor);
+        assert!(state.workflow_editor_component.is_some());
+        let id = WorkflowId::new("local", "attach-a-header-to-an-http-request-with-curl.yaml");
+        let copy = state.get_workflow(&id).unwrap();
+        assert_eq!(copy.command, workflow.command);
+
+        let changed = Workflow {
+            name: "Curl with a header".to_string(),
+            ..copy
+        };
+        assert_eq!(state.apply(Action::Save(Box::new(changed))), Action::None);
+        assert_eq!(state.focus, Focus::List);
+        assert!(state.workflow_editor_component.is_none());
+        assert_eq!(state.message, Some(format!("saved {}", id)));
+        assert_eq!(state.get_workflow(&id).unwrap().name, "Curl with a header");
+        assert_eq!(state.get_workflows().unwrap().len(), 4);
     }
 }
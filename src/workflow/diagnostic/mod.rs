@@ -0,0 +1,67 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// A workflow file that could not be loaded, with the position of the
+/// problem if it is known
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(path: impl Into<PathBuf>, message: impl Display) -> Self {
+        Diagnostic {
+            path: path.into(),
+            line: None,
+            column: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn from_yaml_error(path: &Path, error: &serde_yaml::Error) -> Self {
+        let location = error.location();
+        Diagnostic {
+            path: path.to_path_buf(),
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workflow::diagnostic::Diagnostic;
+    use crate::workflow::file_format::Workflow;
+    use std::path::Path;
+
+    #[test]
+    fn test_diagnostic_from_yaml_error_has_position() {
+        let yaml = "name: a\ncommand: b\ntags: not a list\n";
+        let error = serde_yaml::from_str::<Workflow>(yaml).unwrap_err();
+        let diagnostic = Diagnostic::from_yaml_error(Path::new("a.yaml"), &error);
+        assert_eq!(diagnostic.line, Some(3));
+        assert_eq!(diagnostic.column, Some(7));
+        assert!(diagnostic.to_string().starts_with("a.yaml:3:7: "));
+
+        let diagnostic = Diagnostic::new("b.yaml", "permission denied");
+        assert_eq!(diagnostic.to_string(), "b.yaml: permission denied");
+    }
+}
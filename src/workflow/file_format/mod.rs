@@ -1,12 +1,21 @@
+use clap::ValueEnum;
+use ratatui::text::{Line, Span, Text};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use ratatui::text::{Line, Span, Text};
+use std::path::Path;
 
 use ratatui::widgets::ListItem;
 
-#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash, PartialOrd)]
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::source::{WorkflowId, WorkflowSource};
+use crate::workflow::template;
+use crate::workflow::template::quote::{quote, quote_path, Context};
+use crate::workflow::template::Segment;
+use regex::Regex;
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq, Hash, PartialOrd, ValueEnum)]
 pub enum Shell {
     #[serde(alias = "fish")]
     Fish,
@@ -16,6 +25,64 @@ pub enum Shell {
     Zsh,
 }
 
+impl Display for Shell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Shell::Fish => write!(f, "fish"),
+            Shell::Bash => write!(f, "bash"),
+            Shell::Zsh => write!(f, "zsh"),
+        }
+    }
+}
+
+impl Shell {
+    /// The shell run by `program`, like `/usr/bin/zsh`
+    pub fn from_program(program: &str) -> Option<Shell> {
+        let name = Path::new(program).file_name()?.to_str()?;
+        Shell::from_str(name, true).ok()
+    }
+
+    /// The shell of the user from `$SHELL`. Other shells are assumed to
+    /// follow the POSIX rules of bash.
+    pub fn current() -> Shell {
+        std::env::var("SHELL")
+            .ok()
+            .and_then(|program| Shell::from_program(&program))
+            .unwrap_or(Shell::Bash)
+    }
+}
+
+/// The kind of value an argument takes. Arguments without a type are strings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentType {
+    #[default]
+    String,
+    Int,
+    Bool,
+    Path,
+    /// one of the `options` of the argument
+    Enum,
+}
+
+impl Display for ArgumentType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgumentType::String => write!(f, "string"),
+            ArgumentType::Int => write!(f, "int"),
+            ArgumentType::Bool => write!(f, "bool"),
+            ArgumentType::Path => write!(f, "path"),
+            ArgumentType::Enum => write!(f, "enum"),
+        }
+    }
+}
+
+impl ArgumentType {
+    fn is_string(&self) -> bool {
+        *self == ArgumentType::String
+    }
+}
+
 /// Arguments are the parameters that a workflow can take
 /// They are used to generate a form for the user to fill out
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
@@ -23,17 +90,47 @@ pub struct Argument {
     pub name: String,
     pub description: Option<String>,
     pub default_value: Option<String>,
+    /// insert the value as is instead of quoting it for the shell, for
+    /// values like globs or several flags that the shell has to split
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub raw: bool,
+    #[serde(
+        default,
+        rename = "type",
+        skip_serializing_if = "ArgumentType::is_string"
+    )]
+    pub kind: ArgumentType,
+    /// the values an enum argument can take
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    /// a regular expression the whole value has to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// bounds of an int argument, both inclusive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
 }
 #[allow(dead_code)]
 impl Argument {
     pub fn new(name: impl Into<String>) -> Self {
         Argument {
-            description: None,
             name: name.into(),
-            default_value: None,
+            ..Argument::default()
         }
     }
 
+    pub fn with_type(mut self, kind: ArgumentType) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_options(mut self, options: Vec<String>) -> Self {
+        self.options = options;
+        self
+    }
+
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
         self
@@ -50,6 +147,87 @@ impl Argument {
     pub fn default_value(&self) -> &Option<String> {
         &self.default_value
     }
+
+    fn pattern(&self) -> WorkflowResult<Option<Regex>> {
+        let Some(pattern) = &self.pattern else {
+            return Ok(None);
+        };
+        Regex::new(&format!("^(?:{})$", pattern))
+            .map(Some)
+            .map_err(|e| {
+                WorkflowError::InvalidArgument(format!(
+                    "pattern of {} is no valid regular expression: {}",
+                    self.name, e
+                ))
+            })
+    }
+
+    /// Check that the argument itself makes sense: enums have options, the
+    /// pattern compiles, the bounds are in order and the default is valid
+    pub fn check(&self) -> WorkflowResult<()> {
+        let invalid = |message: String| Err(WorkflowError::InvalidArgument(message));
+        if self.kind == ArgumentType::Enum && self.options.is_empty() {
+            return invalid(format!("enum {} has no options", self.name));
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                return invalid(format!("{} has a min above its max", self.name));
+            }
+        }
+        self.pattern()?;
+        match &self.default_value {
+            Some(default_value) => self.validate(default_value),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that `value` is of the type of the argument and within its
+    /// bounds, options and pattern
+    pub fn validate(&self, value: &str) -> WorkflowResult<()> {
+        let invalid = |message: String| Err(WorkflowError::InvalidArgument(message));
+        match self.kind {
+            ArgumentType::Int => {
+                let Ok(number) = value.trim().parse::<i64>() else {
+                    return invalid(format!("{} must be a whole number", self.name));
+                };
+                if self.min.is_some_and(|min| number < min) {
+                    return invalid(format!(
+                        "{} must be at least {}",
+                        self.name,
+                        self.min.unwrap()
+                    ));
+                }
+                if self.max.is_some_and(|max| number > max) {
+                    return invalid(format!(
+                        "{} must be at most {}",
+                        self.name,
+                        self.max.unwrap()
+                    ));
+                }
+            }
+            ArgumentType::Bool if value != "true" && value != "false" => {
+                return invalid(format!("{} must be true or false", self.name));
+            }
+            ArgumentType::Enum if !self.options.iter().any(|o| o == value) => {
+                return invalid(format!(
+                    "{} must be one of {}",
+                    self.name,
+                    self.options.join(", ")
+                ));
+            }
+            _ => {}
+        }
+        if let Some(pattern) = self.pattern()? {
+            if !pattern.is_match(value) {
+                return invalid(format!(
+                    "{} must match {}",
+                    self.name,
+                    self.pattern.as_deref().unwrap_or_default()
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A workflow is a command that can be run
@@ -68,23 +246,27 @@ pub struct Workflow {
     pub author_url: Option<String>,
     #[serde(default)]
     pub shells: Vec<Shell>,
+    /// set by the repository the workflow was loaded from
+    #[serde(skip)]
+    pub source: Option<WorkflowSource>,
 }
 
-fn  simple_text_line<'a>(content: String) -> Line<'a> {
-    Line{
-        spans: vec![
-            Span{ content: content.into(), style: Default::default() },
-        ],
+fn simple_text_line<'a>(content: String) -> Line<'a> {
+    Line {
+        spans: vec![Span {
+            content: content.into(),
+            style: Default::default(),
+        }],
         style: Default::default(),
         alignment: None,
     }
 }
 
-impl <'a> Into<ListItem<'a>> for Workflow {
-    fn into(self) -> ListItem<'a> {
-        let desc = self.description.unwrap_or_else(|| String::from(""));
-        let name = self.name.clone();
-        ListItem::new(Text{
+impl<'a> From<Workflow> for ListItem<'a> {
+    fn from(workflow: Workflow) -> Self {
+        let desc = workflow.description.unwrap_or_else(|| String::from(""));
+        let name = workflow.name;
+        ListItem::new(Text {
             lines: vec![
                 simple_text_line("------------------------------------------".into()),
                 simple_text_line(name),
@@ -97,30 +279,145 @@ impl <'a> Into<ListItem<'a>> for Workflow {
     }
 }
 
+/// Describes the workflow for humans: name, description and the indented
+/// command, followed by whatever else is known about it
 impl Display for Workflow {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "Name: {}\nCommand: {}\nTags: {:?}\nDescription: {:?}\nArguments: {:?}\nSource URL: {:?}\nAuthor: {:?}\nAuthor URL: {:?}\nShells: {:?}",
-               self.name,
-               self.command,
-               self.tags,
-               self.description,
-               self.arguments,
-               self.source_url,
-               self.author,
-               self.author_url,
-               self.shells
-        )
+        writeln!(f, "{}", self.name)?;
+        if let Some(description) = &self.description {
+            writeln!(f, "{}", description)?;
+        }
+        writeln!(f)?;
+        for line in self.command.trim_end().lines() {
+            writeln!(f, "    {}", line)?;
+        }
+        if !self.arguments.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Arguments:")?;
+            let width = self
+                .arguments
+                .iter()
+                .map(|a| a.name.len())
+                .max()
+                .unwrap_or(0);
+            for argument in &self.arguments {
+                write!(f, "  {:width$}", argument.name, width = width)?;
+                if let Some(description) = &argument.description {
+                    write!(f, "  {}", description)?;
+                }
+                let mut notes = vec![];
+                match argument.kind {
+                    ArgumentType::String => {}
+                    ArgumentType::Enum => {
+                        notes.push(format!("one of {}", argument.options.join(", ")))
+                    }
+                    kind => notes.push(kind.to_string()),
+                }
+                if let Some(default_value) = &argument.default_value {
+                    notes.push(format!("default: {}", default_value));
+                }
+                if !notes.is_empty() {
+                    write!(f, " ({})", notes.join("; "))?;
+                }
+                writeln!(f)?;
+            }
+        }
+        writeln!(f)?;
+        if !self.tags.is_empty() {
+            writeln!(f, "Tags: {}", self.tags.join(", "))?;
+        }
+        if !self.shells.is_empty() {
+            let shells: Vec<String> = self.shells.iter().map(Shell::to_string).collect();
+            writeln!(f, "Shells: {}", shells.join(", "))?;
+        }
+        match (&self.author, &self.author_url) {
+            (Some(author), Some(url)) => writeln!(f, "Author: {} <{}>", author, url)?,
+            (Some(author), None) => writeln!(f, "Author: {}", author)?,
+            (None, Some(url)) => writeln!(f, "Author: {}", url)?,
+            (None, None) => {}
+        }
+        if let Some(source_url) = &self.source_url {
+            writeln!(f, "Source: {}", source_url)?;
+        }
+        if let Some(id) = self.id() {
+            writeln!(f, "Id: {}", id)?;
+        }
+        Ok(())
     }
 }
 
 #[allow(dead_code)]
 impl Workflow {
-    pub fn render(&self, values: HashMap<&str, &str>) -> String {
-        let mut command = self.command.clone();
-        for (key, arg) in values {
-            command = command.replace(&format!("{{{{{}}}}}", key), arg);
+    /// Check that the command fits the arguments and that the arguments
+    /// make sense
+    pub fn check(&self) -> WorkflowResult<()> {
+        let segments = template::parse(&self.command)?;
+        template::check(&segments, &self.arguments)?;
+        self.arguments.iter().try_for_each(Argument::check)
+    }
+
+    /// The segments of the command, with a value in `values` for every
+    /// placeholder. Placeholders and arguments that do not fit each other
+    /// are reported by `check`, they do not keep a command from running.
+    fn segments(&self, values: &HashMap<&str, &str>) -> WorkflowResult<Vec<Segment<'_>>> {
+        let segments = template::parse(&self.command)?;
+        let missing: Vec<&str> = template::placeholders(&segments)
+            .into_iter()
+            .filter(|name| !values.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(WorkflowError::InvalidArgument(format!(
+                "missing value(s) for {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(segments)
+    }
+
+    /// Replace the placeholders with `values` as they are
+    pub fn render(&self, values: HashMap<&str, &str>) -> WorkflowResult<String> {
+        let mut command = String::with_capacity(self.command.len());
+        for segment in self.segments(&values)? {
+            match segment {
+                Segment::Text(text) => command.push_str(text),
+                Segment::Placeholder(name) => command.push_str(values[name]),
+            }
         }
-        command
+        Ok(command)
+    }
+
+    /// Like `render`, but every value is quoted so that `shell` reads it as
+    /// a single word, depending on the quotes around its placeholder.
+    /// Values of raw arguments are inserted as they are.
+    pub fn render_quoted(
+        &self,
+        values: HashMap<&str, &str>,
+        shell: &Shell,
+    ) -> WorkflowResult<String> {
+        let mut command = String::with_capacity(self.command.len());
+        let mut context = Context::Unquoted;
+        for segment in self.segments(&values)? {
+            match segment {
+                Segment::Text(text) => {
+                    command.push_str(text);
+                    context = context.after(text, shell);
+                }
+                Segment::Placeholder(name) => {
+                    let value = values[name];
+                    // segments() made sure that every placeholder has one,
+                    // placeholders without an argument are quoted
+                    let argument = self.arguments.iter().find(|a| a.name == name);
+                    match argument.map(|a| (a.raw, a.kind)) {
+                        Some((true, _)) => command.push_str(value),
+                        Some((false, ArgumentType::Path)) => {
+                            command.push_str(&quote_path(value, shell, context))
+                        }
+                        _ => command.push_str(&quote(value, shell, context)),
+                    }
+                }
+            }
+        }
+        Ok(command)
     }
 
     pub fn name(&self) -> &str {
@@ -155,6 +452,14 @@ impl Workflow {
         &self.shells
     }
 
+    pub fn source(&self) -> &Option<WorkflowSource> {
+        &self.source
+    }
+
+    pub fn id(&self) -> Option<&WorkflowId> {
+        self.source.as_ref().map(|source| &source.id)
+    }
+
     pub fn new(name: impl Into<String>, command: impl Into<String>) -> Self {
         Workflow {
             name: name.into(),
@@ -166,6 +471,7 @@ impl Workflow {
             author: None,
             author_url: None,
             shells: vec![],
+            source: None,
         }
     }
 
@@ -1,12 +1,17 @@
 /// highly inspired and mostly copied from
 /// https://github.com/warpdotdev/workflows/blob/main/workflow-types/src/lib.rs
+pub mod diagnostic;
 pub mod file_format;
 pub mod repository;
+pub mod search;
+pub mod source;
+pub mod template;
 
 #[cfg(test)]
 mod tests {
-    use crate::workflow::file_format::Workflow;
-    use serde_yaml;
+    use crate::prelude::WorkflowError;
+    use crate::workflow::file_format::{Argument, ArgumentType, Shell, Workflow};
+    use std::collections::HashMap;
     use std::fs::File;
     use std::io::BufReader;
     #[test]
@@ -25,18 +30,131 @@ mod tests {
         assert_eq!(workflow.tags, vec!["curl".to_string()]);
     }
 
+    #[test]
+    fn test_display_workflow() {
+        let file = File::open("tests/fixtures/sample.yaml").unwrap();
+        let workflow: Workflow = serde_yaml::from_reader(BufReader::new(file)).unwrap();
+        assert_eq!(
+            workflow.to_string(),
+            "Attach a header to an HTTP request with cURL\n\
+             Uses the --header flag to attach an additional header when sending a request to a url.\n\
+             \n    curl --header \"{{header}}\" {{url}}\n    demo\n\
+             \nArguments:\n  \
+             header  The extra header that should be attached.\n  \
+             url     The url where the request should be sent.\n\
+             \nTags: curl\n\
+             Author: Tader <https://stackoverflow.com/users/30700/tader>\n\
+             Source: https://stackoverflow.com/questions/356705/how-to-send-a-header-using-a-http-request-through-a-curl-call\n"
+        );
+    }
+
+    #[test]
+    fn test_typed_arguments() {
+        let yaml = "name: Log\ncommand: git log -n {{count}}\narguments:\n  \
+                    - name: count\n    type: int\n    min: 1\n    max: 100\n  \
+                    - name: format\n    type: enum\n    options: [oneline, full]\n  \
+                    - name: text\n    pattern: \"[a-z]+\"\n";
+        let workflow: Workflow = serde_yaml::from_str(yaml).unwrap();
+        let [count, format, text] = &workflow.arguments[..] else {
+            panic!("expected three arguments");
+        };
+        assert_eq!(count.kind, ArgumentType::Int);
+        assert!(count.validate("100").is_ok());
+        assert_eq!(
+            count.validate("101").unwrap_err().to_string(),
+            "invalid argument: count must be at most 100"
+        );
+        assert!(count.validate("ten").is_err());
+        assert!(format.validate("full").is_ok());
+        assert!(format.validate("short").is_err());
+        assert!(text.validate("abc").is_ok());
+        assert!(text.validate("abc1").is_err());
+        assert!(workflow
+            .to_string()
+            .contains("\n  count  (int)\n  format (one of oneline, full)\n"));
+
+        // files without types stay untyped and are written without them
+        let file = File::open("tests/fixtures/sample.yaml").unwrap();
+        let workflow: Workflow = serde_yaml::from_reader(BufReader::new(file)).unwrap();
+        assert_eq!(workflow.arguments[0].kind, ArgumentType::String);
+        assert!(!serde_yaml::to_string(&workflow).unwrap().contains("type"));
+
+        let broken = Argument::new("mode").with_type(ArgumentType::Enum);
+        assert!(broken.check().is_err());
+        let mut bounds = Argument::new("count").with_type(ArgumentType::Int);
+        bounds.default_value = Some("0".to_string());
+        bounds.min = Some(1);
+        assert!(bounds.check().is_err());
+    }
+
     #[test]
     fn test_render_workflow() {
         let workflow = Workflow {
             command: "curl --header {{header}} \"{{url}}\"".to_string(),
+            arguments: vec![Argument::new("header"), Argument::new("url")],
             ..Workflow::default()
         };
         let mut values: std::collections::HashMap<&str, &str> = std::collections::HashMap::new();
         values.insert("header", "Authorization: Bearer 123");
         values.insert("url", "https://www.google.de");
         assert_eq!(
-            workflow.render(values),
+            workflow.render(values).unwrap(),
             "curl --header Authorization: Bearer 123 \"https://www.google.de\""
         );
     }
+
+    #[test]
+    fn test_render_reports_template_errors() {
+        // an unused argument is reported by check, but still renders
+        let workflow = Workflow::new("Fetch", "curl {{url}}")
+            .with_arguments(vec![Argument::new("url"), Argument::new("header")]);
+        assert_eq!(
+            workflow.render(HashMap::from([("url", "a")])).unwrap(),
+            "curl a"
+        );
+        assert_eq!(
+            workflow
+                .render_quoted(HashMap::from([("url", "a b")]), &Shell::Bash)
+                .unwrap(),
+            "curl 'a b'"
+        );
+        assert!(matches!(workflow.check(), Err(WorkflowError::Template(_))));
+
+        let workflow = Workflow::new("Fetch", "curl {{url}} {{header}}")
+            .with_arguments(vec![Argument::new("url"), Argument::new("header")]);
+        let error = workflow.render(HashMap::from([("url", "a")])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid argument: missing value(s) for header"
+        );
+
+        let workflow = Workflow::new("Fetch", "curl {{url");
+        assert!(workflow.render(HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_render_workflow_quoted() {
+        let mut pattern = Argument::new("pattern");
+        pattern.raw = true;
+        let workflow = Workflow {
+            command: "curl --header {{header}} \"{{url}}\" && ls {{pattern}}".to_string(),
+            arguments: vec![Argument::new("header"), Argument::new("url"), pattern],
+            ..Workflow::default()
+        };
+        let values = || {
+            HashMap::from([
+                ("header", "Authorization: Bearer '123'"),
+                ("url", "https://a.b/?q=\"$(id)\""),
+                ("pattern", "*.rs"),
+            ])
+        };
+        assert_eq!(
+            workflow.render_quoted(values(), &Shell::Bash).unwrap(),
+            r#"curl --header 'Authorization: Bearer '\''123'\''' "https://a.b/?q=\"\$(id)\"" && ls *.rs"#
+        );
+        assert_eq!(
+            workflow.render_quoted(values(), &Shell::Fish).unwrap(),
+            r#"curl --header 'Authorization: Bearer \'123\'' "https://a.b/?q=\"\$(id)\"" && ls *.rs"#
+        );
+    }
 }
@@ -0,0 +1,323 @@
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::diagnostic::Diagnostic;
+use crate::workflow::file_format::Workflow;
+use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+use crate::workflow::search;
+use crate::workflow::search::WorkflowMatch;
+use crate::workflow::source::WorkflowId;
+
+/// An error reported by one of the sources of a [`CompositeRepository`]
+#[derive(Debug, Clone)]
+pub struct SourceError {
+    pub source: String,
+    pub error: WorkflowError,
+}
+
+/// Combines any number of repositories into one.
+///
+/// Repositories are kept in priority order: lookups are answered by the
+/// first repository that knows a workflow, listings and queries merge the
+/// results of all repositories. Changed workflows are saved to the
+/// repository they came from. New workflows, and those of repositories that
+/// are not writable, go to the write target, the first writable repository
+/// unless configured otherwise.
+#[derive(Default)]
+pub struct CompositeRepository {
+    repositories: Vec<Box<dyn WorkflowRepository>>,
+    write_target: Option<String>,
+    errors: Vec<SourceError>,
+}
+
+impl CompositeRepository {
+    pub fn new(repositories: Vec<Box<dyn WorkflowRepository>>) -> Self {
+        CompositeRepository {
+            repositories,
+            write_target: None,
+            errors: vec![],
+        }
+    }
+
+    /// Save workflows to the repository with the id `write_target`
+    pub fn with_write_target(mut self, write_target: impl Into<String>) -> Self {
+        self.write_target = Some(write_target.into());
+        self
+    }
+
+    /// Add a repository with a lower priority than all others
+    pub fn push(&mut self, repository: Box<dyn WorkflowRepository>) {
+        self.repositories.push(repository);
+    }
+
+    /// Record an error of a source that could not be added at all
+    pub fn push_error(&mut self, source: impl Into<String>, error: WorkflowError) {
+        self.errors.push(SourceError {
+            source: source.into(),
+            error,
+        });
+    }
+
+    /// Errors of the sources that failed to load or refresh
+    pub fn errors(&self) -> &Vec<SourceError> {
+        &self.errors
+    }
+
+    fn repository_mut(&mut self, id: &str) -> WorkflowResult<&mut Box<dyn WorkflowRepository>> {
+        self.repositories
+            .iter_mut()
+            .find(|r| r.id() == id)
+            .ok_or_else(|| WorkflowError::NotFound(format!("workflow source {}", id)))
+    }
+}
+
+impl WorkflowRepository for CompositeRepository {
+    fn id(&self) -> &str {
+        "all"
+    }
+
+    /// Refresh every source, even if some fail. The failures are kept in
+    /// [`CompositeRepository::errors`] and returned with the changes of the
+    /// other sources.
+    fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+        self.errors.clear();
+        let mut changes = ChangeSet::default();
+        for repository in self.repositories.iter_mut() {
+            match repository.refresh() {
+                Ok(changed) => changes.extend(changed),
+                Err(error) => self.errors.push(SourceError {
+                    source: repository.id().to_string(),
+                    error,
+                }),
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(changes)
+        } else {
+            Err(WorkflowError::Refresh {
+                changes,
+                errors: self.errors.clone(),
+            })
+        }
+    }
+
+    fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+        self.repositories
+            .iter()
+            .find_map(|r| r.get_workflow(id).ok())
+            .ok_or_else(|| WorkflowError::NotFound(id.to_string()))
+    }
+
+    fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>> {
+        let mut all = vec![];
+        for repository in self.repositories.iter() {
+            all.append(&mut repository.get_workflows()?);
+        }
+        Ok(all)
+    }
+
+    fn is_writable(&self) -> bool {
+        self.repositories.iter().any(|r| r.is_writable())
+    }
+
+    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<WorkflowId> {
+        let source = workflow.id().map(|id| id.repository.clone());
+        let own = self
+            .repositories
+            .iter_mut()
+            .find(|r| Some(r.id()) == source.as_deref() && r.is_writable());
+        if let Some(repository) = own {
+            return repository.save_workflow(workflow);
+        }
+        match self.write_target.clone() {
+            Some(target) => self.repository_mut(&target)?.save_workflow(workflow),
+            None => match self.repositories.iter_mut().find(|r| r.is_writable()) {
+                Some(repository) => repository.save_workflow(workflow),
+                None => Err(WorkflowError::InvalidArgument(
+                    "no writable workflow source is configured".to_string(),
+                )),
+            },
+        }
+    }
+
+    /// Delete a workflow of a writable source, the files of other sources
+    /// would come back on the next refresh
+    fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+        let repository = self.repository_mut(&id.repository)?;
+        if !repository.is_writable() {
+            return Err(WorkflowError::InvalidArgument(format!(
+                "{} is read-only, unable to delete {}",
+                id.repository, id
+            )));
+        }
+        repository.delete_workflow(id)
+    }
+
+    /// Matches of all sources ranked together, on equal scores the source
+    /// with the higher priority comes first
+    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+        let mut result = vec![];
+        for repository in self.repositories.iter() {
+            result.append(&mut repository.query_workflows(query)?);
+        }
+        search::sort(&mut result);
+        Ok(result)
+    }
+
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.repositories
+            .iter()
+            .flat_map(|r| r.diagnostics())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::{WorkflowError, WorkflowResult};
+    use crate::workflow::diagnostic::Diagnostic;
+    use crate::workflow::file_format::Workflow;
+    use crate::workflow::repository::composite::CompositeRepository;
+    use crate::workflow::repository::directory::DirectoryRepository;
+    use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+    use crate::workflow::search::WorkflowMatch;
+    use crate::workflow::source::WorkflowId;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    /// A source that can not be refreshed or written to
+    struct Broken;
+
+    impl WorkflowRepository for Broken {
+        fn id(&self) -> &str {
+            "broken"
+        }
+        fn is_writable(&self) -> bool {
+            false
+        }
+        fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+            Err(WorkflowError::Git {
+                path: None,
+                message: "offline".to_string(),
+            })
+        }
+        fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+            Err(WorkflowError::NotFound(id.to_string()))
+        }
+        fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>> {
+            Ok(vec![])
+        }
+        fn save_workflow(&mut self, _workflow: Workflow) -> WorkflowResult<WorkflowId> {
+            Err(WorkflowError::Git {
+                path: None,
+                message: "offline".to_string(),
+            })
+        }
+        fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+            Err(WorkflowError::NotFound(id.to_string()))
+        }
+        fn query_workflows(&self, _query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+            Ok(vec![])
+        }
+        fn diagnostics(&self) -> Vec<Diagnostic> {
+            vec![]
+        }
+    }
+
+    fn fixtures(id: &str) -> Box<dyn WorkflowRepository> {
+        Box::new(DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows")).with_id(id))
+    }
+
+    #[test]
+    fn test_merges_all_sources() {
+        let repo = CompositeRepository::new(vec![fixtures("a"), fixtures("b")]);
+        assert_eq!(repo.get_workflows().unwrap().len(), 6);
+        assert_eq!(repo.query_workflows("curl").unwrap().len(), 2);
+        assert!(repo.query_workflows("no such workflow").unwrap().is_empty());
+
+        let b = repo
+            .get_workflow(&WorkflowId::new("b", "sample.yaml"))
+            .unwrap();
+        assert_eq!(b.id(), Some(&WorkflowId::new("b", "sample.yaml")));
+    }
+
+    #[test]
+    fn test_saves_to_write_target() {
+        let dir = TempDir::new().unwrap();
+        let local = DirectoryRepository::new(dir.path().to_path_buf()).with_id("mine");
+        let mut repo = CompositeRepository::new(vec![fixtures("shared"), Box::new(local)])
+            .with_write_target("mine");
+
+        repo.save_workflow(Workflow::new("test", "echo test"))
+            .unwrap();
+        assert!(dir.path().join("test.yaml").is_file());
+        assert!(repo
+            .get_workflow(&WorkflowId::new("mine", "test.yaml"))
+            .is_ok());
+
+        repo.delete_workflow(&WorkflowId::new("mine", "test.yaml"))
+            .unwrap();
+        assert!(!dir.path().join("test.yaml").exists());
+    }
+
+    #[test]
+    fn test_saves_to_the_first_writable_source() {
+        let mut repo = CompositeRepository::new(vec![Box::new(Broken)]);
+        let saved = repo.save_workflow(Workflow::new("test", "echo test"));
+        assert!(matches!(saved, Err(WorkflowError::InvalidArgument(_))));
+
+        let dir = TempDir::new().unwrap();
+        repo.push(Box::new(DirectoryRepository::new(dir.path().to_path_buf())));
+        let id = repo
+            .save_workflow(Workflow::new("test", "echo test"))
+            .unwrap();
+        assert_eq!(id, WorkflowId::new("local", "test.yaml"));
+        assert!(dir.path().join("test.yaml").is_file());
+    }
+
+    #[test]
+    fn test_read_only_workflows_are_not_deleted() {
+        let mut repo = CompositeRepository::new(vec![Box::new(Broken)]);
+        let deleted = repo.delete_workflow(&WorkflowId::new("broken", "a.yaml"));
+        assert!(matches!(deleted, Err(WorkflowError::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn test_changes_stay_in_their_source() {
+        let team = TempDir::new().unwrap();
+        let mine = TempDir::new().unwrap();
+        std::fs::copy(
+            "tests/fixtures/workflows/sample.yaml",
+            team.path().join("sample.yaml"),
+        )
+        .unwrap();
+        let mut repo = CompositeRepository::new(vec![
+            Box::new(DirectoryRepository::new(team.path().to_path_buf()).with_id("team")),
+            Box::new(DirectoryRepository::new(mine.path().to_path_buf()).with_id("mine")),
+        ])
+        .with_write_target("mine");
+
+        let mut workflow = repo
+            .get_workflow(&WorkflowId::new("team", "sample.yaml"))
+            .unwrap();
+        workflow.command = "echo changed".to_string();
+        let id = repo.save_workflow(workflow).unwrap();
+        assert_eq!(id, WorkflowId::new("team", "sample.yaml"));
+        assert_eq!(std::fs::read_dir(mine.path()).unwrap().count(), 0);
+        assert_eq!(repo.get_workflow(&id).unwrap().command, "echo changed");
+    }
+
+    #[test]
+    fn test_collects_refresh_errors_per_source() {
+        let mut repo = CompositeRepository::new(vec![Box::new(Broken), fixtures("local")]);
+
+        match repo.refresh() {
+            Err(WorkflowError::Refresh { changes, errors }) => {
+                assert!(changes.is_empty());
+                assert_eq!(errors[0].error.to_string(), "git error: offline");
+            }
+            _ => panic!("the broken source should be reported"),
+        }
+        assert_eq!(repo.errors().len(), 1);
+        assert_eq!(repo.errors()[0].source, "broken");
+        assert_eq!(repo.get_workflows().unwrap().len(), 3);
+    }
+}
@@ -1,42 +1,131 @@
 use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::diagnostic::Diagnostic;
 use crate::workflow::file_format::Workflow;
-use crate::workflow::repository::WorkflowRepository;
+use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+use crate::workflow::search;
+use crate::workflow::search::WorkflowMatch;
+use crate::workflow::source::{WorkflowId, WorkflowSource};
 use di::injectable;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[injectable(WorkflowRepository)]
 #[derive(Debug, Default)]
 pub struct DirectoryRepository {
+    id: String,
     root: PathBuf,
     workflows: Vec<Workflow>,
+    // files that could not be loaded on the last scan
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Turn a workflow name into a file name friendly slug,
+/// e.g. "Attach a header!" becomes "attach-a-header"
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "workflow".to_string()
+    } else {
+        slug.to_string()
+    }
 }
 
 impl DirectoryRepository {
     pub fn new(root: PathBuf) -> Self {
-        let workflows = Self::visit_dir(root.clone()).unwrap();
-        DirectoryRepository { root, workflows }
+        let id = "local".to_string();
+        let (workflows, diagnostics) = Self::load(&id, &root).unwrap();
+        DirectoryRepository {
+            id,
+            root,
+            workflows,
+            diagnostics,
+        }
     }
 
-    fn visit_dir(path_buf: PathBuf) -> WorkflowResult<Vec<Workflow>> {
-        let mut workflows = vec![];
-        let path_str = path_buf.as_path().display().to_string();
-        for entry in std::fs::read_dir(&path_buf)
-            .map_err(|e| WorkflowError::NotFound(format!("{}{:?}", path_str.clone(), e)))?
+    /// Use `id` instead of `local` to identify the workflows of this
+    /// repository.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        for workflow in self.workflows.iter_mut() {
+            if let Some(source) = workflow.source.as_mut() {
+                source.id.repository = self.id.clone();
+            }
+        }
+        self
+    }
+
+    fn source_for(&self, file: PathBuf) -> WorkflowSource {
+        WorkflowSource::new(WorkflowId::for_file(&self.id, &self.root, &file), file)
+    }
+
+    fn file_of(workflow: &Workflow) -> Option<&PathBuf> {
+        workflow.source.as_ref().map(|source| &source.file)
+    }
+
+    /// Find a file below `root` not used by any other workflow yet
+    fn new_file_for(&self, name: &str) -> PathBuf {
+        let slug = slug(name);
+        let mut path = self.root.join(format!("{}.yaml", slug));
+        let mut counter = 1;
+        while path.exists()
+            || self
+                .workflows
+                .iter()
+                .any(|w| Self::file_of(w) == Some(&path))
         {
-            let entry = entry.map_err(|_| WorkflowError::NotFound(path_str.clone()))?; // unwrap the result
+            counter += 1;
+            path = self.root.join(format!("{}-{}.yaml", slug, counter));
+        }
+        path
+    }
+
+    fn load(id: &str, root: &Path) -> WorkflowResult<(Vec<Workflow>, Vec<Diagnostic>)> {
+        let mut diagnostics = vec![];
+        let workflows = Self::visit_dir(root.to_path_buf(), &mut diagnostics)?
+            .into_iter()
+            .map(|(file, mut workflow)| {
+                let id = WorkflowId::for_file(id, root, &file);
+                workflow.source = Some(WorkflowSource::new(id, file));
+                workflow
+            })
+            .collect();
+        Ok((workflows, diagnostics))
+    }
+
+    fn visit_dir(
+        path_buf: PathBuf,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> WorkflowResult<Vec<(PathBuf, Workflow)>> {
+        let mut workflows = vec![];
+        for entry in std::fs::read_dir(&path_buf).map_err(|e| WorkflowError::io(&path_buf, e))? {
+            let entry = entry.map_err(|e| WorkflowError::io(&path_buf, e))?; // unwrap the result
             let path = entry.path();
             if path.is_file() {
                 if let Some(ext) = path.extension() {
                     if ext == "yaml" || ext == "yml" {
-                        let file = std::fs::File::open(path).unwrap();
+                        let file = match std::fs::File::open(&path) {
+                            Ok(file) => file,
+                            Err(e) => {
+                                diagnostics.push(Diagnostic::new(&path, e));
+                                continue;
+                            }
+                        };
                         let reader = std::io::BufReader::new(file);
-                        if let Ok(workflow) = serde_yaml::from_reader(reader) {
-                            workflows.push(workflow);
+                        match serde_yaml::from_reader(reader) {
+                            Ok(workflow) => workflows.push((path, workflow)),
+                            Err(e) => diagnostics.push(Diagnostic::from_yaml_error(&path, &e)),
                         }
                     }
                 }
             } else {
-                let mut sub_workflows = Self::visit_dir(path)?;
+                let mut sub_workflows = Self::visit_dir(path, diagnostics)?;
                 workflows.append(&mut sub_workflows);
             }
         }
@@ -45,17 +134,26 @@ impl DirectoryRepository {
 }
 
 impl WorkflowRepository for DirectoryRepository {
-    fn refresh(&mut self) -> WorkflowResult<()> {
-        self.workflows = Self::visit_dir(self.root.clone())?;
-        Ok(())
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+        let (workflows, diagnostics) = Self::load(&self.id, &self.root)?;
+        let changes = ChangeSet::between(&self.workflows, &workflows);
+        (self.workflows, self.diagnostics) = (workflows, diagnostics);
+        Ok(changes)
     }
-    fn get_workflow(&self, name: &str) -> WorkflowResult<Workflow> {
-        // move name into owned String
-        let name = name.into();
-        let a = self.workflows.iter().find(|w| w.name == name);
+
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics.to_vec()
+    }
+
+    fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+        let a = self.workflows.iter().find(|w| w.id() == Some(id));
         match a {
             Some(w) => Ok(w.clone()),
-            None => Err(WorkflowError::NotFound(name)),
+            None => Err(WorkflowError::NotFound(id.to_string())),
         }
     }
 
@@ -64,43 +162,73 @@ impl WorkflowRepository for DirectoryRepository {
         Ok(a)
     }
 
-    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<()> {
-        // make sure to delete first. no need to update yet
-        let _ = self.delete_workflow(workflow.name());
-        // save as new entry
-        self.workflows.push(workflow.clone());
-        Ok(())
-    }
+    fn save_workflow(&mut self, mut workflow: Workflow) -> WorkflowResult<WorkflowId> {
+        // overwrite the file an existing workflow was loaded from
+        let existing = workflow
+            .id()
+            .and_then(|id| self.workflows.iter().position(|w| w.id() == Some(id)));
+        let path = match existing {
+            Some(index) => self.workflows.remove(index).source.map(|s| s.file),
+            None => None,
+        }
+        .unwrap_or_else(|| self.new_file_for(workflow.name()));
 
-    fn delete_workflow(&mut self, name: &str) -> WorkflowResult<()> {
-        self.workflows.retain(|w| w.name != name);
-        Ok(())
+        let yaml = serde_yaml::to_string(&workflow).map_err(|e| WorkflowError::parse(&path, e))?;
+        std::fs::create_dir_all(&self.root).map_err(|e| WorkflowError::io(&self.root, e))?;
+        std::fs::write(&path, format!("---\n{}", yaml)).map_err(|e| WorkflowError::io(&path, e))?;
+        let source = self.source_for(path);
+        let id = source.id.clone();
+        workflow.source = Some(source);
+        self.workflows.push(workflow);
+        Ok(id)
     }
 
-    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<Workflow>> {
-        let a: Vec<Workflow> = self
+    fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+        let index = self
             .workflows
             .iter()
-            .filter(|w| {
-                w.name.contains(query)
-                    || w.command.contains(query)
-                    || w.tags.contains(&query.to_string())
-            })
-            .cloned()
-            .collect();
-        if !a.is_empty() {
-            Ok(a)
-        } else {
-            Err(WorkflowError::NotFound(query.to_string()))
+            .position(|w| w.id() == Some(id))
+            .ok_or_else(|| WorkflowError::NotFound(id.to_string()))?;
+        if let Some(file) = Self::file_of(&self.workflows[index]) {
+            std::fs::remove_file(file).map_err(|e| WorkflowError::io(file, e))?;
         }
+        self.workflows.remove(index);
+        Ok(())
+    }
+
+    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+        Ok(search::rank(&self.workflows, query))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::workflow::repository::directory::DirectoryRepository;
-    use crate::workflow::repository::WorkflowRepository;
-    use std::path::PathBuf;
+    use crate::workflow::file_format::Workflow;
+    use crate::workflow::repository::directory::{slug, DirectoryRepository};
+    use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+    use crate::workflow::source::WorkflowId;
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &target);
+            } else {
+                std::fs::copy(&path, &target).unwrap();
+            }
+        }
+    }
+
+    /// Copy of tests/fixtures/workflows, so tests can write to it
+    fn fixtures() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        copy_dir(Path::new("tests/fixtures/workflows"), dir.path());
+        dir
+    }
 
     #[test]
     fn test_load_from_dir() {
@@ -113,14 +241,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_query_is_fuzzy_and_ranked() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let found = repo.query_workflows("HTTP hdr").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].workflow.name(),
+            "Attach a header to an HTTP request with cURL"
+        );
+
+        // both demos match in their name, cURL only by scattered letters
+        let found = repo.query_workflows("demo").unwrap();
+        let names: Vec<&str> = found.iter().map(|m| m.workflow.name()).collect();
+        assert_eq!(names[..2], ["Simple Demo", "Simple Demo"]);
+        assert!(found[1].score > found[2].score);
+        assert_eq!(repo.query_workflows("").unwrap().len(), 3);
+    }
+
     #[test]
     fn test_adding_should_add_new_workflow() {
-        let mut repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
 
-        if let Err(e) = repo.save_workflow(crate::workflow::file_format::Workflow::new(
-            "test",
-            "echo test",
-        )) {
+        if let Err(e) = repo.save_workflow(Workflow::new("test", "echo test")) {
             panic!("Error saving workflow: {:?}", e);
         }
 
@@ -134,16 +278,14 @@ mod tests {
 
     #[test]
     fn test_adding_and_delete_should_remove_workflow() {
-        let mut repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
 
-        if let Err(e) = repo.save_workflow(crate::workflow::file_format::Workflow::new(
-            "test",
-            "echo test",
-        )) {
+        if let Err(e) = repo.save_workflow(Workflow::new("test", "echo test")) {
             panic!("Error saving workflow: {:?}", e);
         }
 
-        if let Err(e) = repo.delete_workflow("test") {
+        if let Err(e) = repo.delete_workflow(&WorkflowId::new("local", "test.yaml")) {
             panic!("Error saving workflow: {:?}", e);
         }
 
@@ -157,4 +299,145 @@ mod tests {
 
         let _ = repo.refresh();
     }
+
+    #[test]
+    fn test_saved_workflow_survives_refresh() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+
+        let id = repo
+            .save_workflow(Workflow::new("My Test: Workflow!", "echo test"))
+            .unwrap();
+        assert_eq!(id, WorkflowId::new("local", "my-test-workflow.yaml"));
+        assert!(dir.path().join("my-test-workflow.yaml").is_file());
+
+        repo.refresh().unwrap();
+        assert_eq!(repo.get_workflows().unwrap().len(), 4);
+        assert_eq!(
+            repo.get_workflow(&WorkflowId::new("local", "my-test-workflow.yaml"))
+                .unwrap()
+                .command,
+            "echo test"
+        );
+    }
+
+    #[test]
+    fn test_refresh_reports_changed_files() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+        assert!(repo.refresh().unwrap().is_empty());
+
+        let demo = dir.path().join("demo2.yaml");
+        let text = std::fs::read_to_string(&demo).unwrap();
+        std::fs::write(&demo, text.replace("ls -al", "ls -l")).unwrap();
+        std::fs::remove_file(dir.path().join("sample.yaml")).unwrap();
+        std::fs::write(dir.path().join("new.yaml"), "name: New\ncommand: ls\n").unwrap();
+
+        assert_eq!(
+            repo.refresh().unwrap(),
+            ChangeSet {
+                added: vec![WorkflowId::new("local", "new.yaml")],
+                modified: vec![WorkflowId::new("local", "demo2.yaml")],
+                removed: vec![WorkflowId::new("local", "sample.yaml")],
+            }
+        );
+    }
+
+    #[test]
+    fn test_deleted_workflow_stays_deleted_after_refresh() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+
+        repo.delete_workflow(&WorkflowId::new("local", "sample.yaml"))
+            .unwrap();
+        assert!(!dir.path().join("sample.yaml").exists());
+
+        repo.refresh().unwrap();
+        assert_eq!(repo.get_workflows().unwrap().len(), 2);
+        assert!(repo
+            .delete_workflow(&WorkflowId::new("local", "does-not-exist.yaml"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_saving_existing_workflow_overwrites_its_file() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+        let id = WorkflowId::new("local", "sample.yaml");
+
+        let mut workflow = repo.get_workflow(&id).unwrap();
+        workflow.command = "curl {{url}}".to_string();
+        repo.save_workflow(workflow).unwrap();
+
+        let reloaded = DirectoryRepository::new(dir.path().to_path_buf());
+        assert_eq!(reloaded.get_workflows().unwrap().len(), 3);
+        assert_eq!(reloaded.get_workflow(&id).unwrap().command, "curl {{url}}");
+        assert!(std::fs::read_to_string(dir.path().join("sample.yaml"))
+            .unwrap()
+            .contains("curl {{url}}"));
+    }
+
+    #[test]
+    fn test_new_files_do_not_clash() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("test.yaml"), "not a workflow").unwrap();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+
+        repo.save_workflow(Workflow::new("test", "echo test"))
+            .unwrap();
+        assert!(dir.path().join("test-2.yaml").is_file());
+        assert_eq!(slug("  --  "), "workflow");
+    }
+
+    #[test]
+    fn test_workflows_with_same_name_are_addressed_by_id() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf()).with_id("mine");
+        let top = WorkflowId::new("mine", "demo2.yaml");
+        let sub = WorkflowId::new("mine", "sub/demo3.yaml");
+
+        let workflow = repo.get_workflow(&sub).unwrap();
+        assert_eq!(workflow.name, "Simple Demo");
+        let source = workflow.source.unwrap();
+        assert_eq!(source.file, dir.path().join("sub").join("demo3.yaml"));
+        assert_eq!(source.commit, None);
+
+        repo.delete_workflow(&top).unwrap();
+        assert!(repo.get_workflow(&top).is_err());
+        assert!(repo.get_workflow(&sub).is_ok());
+        assert!(dir.path().join("sub").join("demo3.yaml").exists());
+    }
+
+    #[test]
+    fn test_saving_workflow_of_other_repository_creates_new_file() {
+        let dir = fixtures();
+        let mut repo = DirectoryRepository::new(dir.path().to_path_buf());
+        let mut workflow = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"))
+            .with_id("other")
+            .get_workflow(&WorkflowId::new("other", "demo2.yaml"))
+            .unwrap();
+        workflow.command = "ls".to_string();
+
+        repo.save_workflow(workflow).unwrap();
+        let saved = WorkflowId::new("local", "simple-demo.yaml");
+        assert_eq!(repo.get_workflow(&saved).unwrap().command, "ls");
+        assert_eq!(repo.get_workflows().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_files_are_reported() {
+        let repo = DirectoryRepository::new(PathBuf::from("tests/fixtures/invalid"));
+        assert_eq!(repo.get_workflows().unwrap().len(), 1);
+
+        let mut diagnostics = repo.diagnostics();
+        diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].path.ends_with("broken_syntax.yaml"));
+        assert!(diagnostics[0].line.is_some());
+        assert!(diagnostics[1].path.ends_with("missing_command.yaml"));
+        assert!(diagnostics[1].message.contains("missing field `command`"));
+
+        let valid = DirectoryRepository::new(PathBuf::from("tests/fixtures/workflows"));
+        assert!(valid.diagnostics().is_empty());
+    }
 }
@@ -1,88 +1,466 @@
 use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::diagnostic::Diagnostic;
 use crate::workflow::file_format::Workflow;
 use crate::workflow::repository::directory::DirectoryRepository;
-use crate::workflow::repository::WorkflowRepository;
+use crate::workflow::repository::{ChangeSet, WorkflowRepository};
+use crate::workflow::search::WorkflowMatch;
+use crate::workflow::source::WorkflowId;
 use di::injectable;
 use git2::build::{CheckoutBuilder, RepoBuilder};
-use std::path::PathBuf;
+use git2::{BranchType, Delta, Repository};
+use std::path::{Path, PathBuf};
+
+fn is_workflow_file(path: &Path) -> bool {
+    matches!(path.extension(), Some(ext) if ext == "yaml" || ext == "yml")
+}
+
+/// Name of the repository a url points to,
+/// e.g. "https://github.com/warpdotdev/workflows.git" becomes "workflows"
+pub fn id_from_url(url: &str) -> String {
+    let trimmed = url.trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix(".git").unwrap_or(trimmed);
+    trimmed
+        .rsplit(['/', ':', '\\'])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("git")
+        .to_string()
+}
+
 #[injectable(WorkflowRepository)]
-#[allow(dead_code)]
 pub struct GitRepository {
+    id: String,
     url: String,
     branch: String,
     root: PathBuf,
+    commit: String,
     directory_repository: DirectoryRepository,
 }
 
 impl GitRepository {
-    #[doc = r"Create a new GitRepository"]
-    pub fn new(url: &str, branch: &str, path_buf: PathBuf) -> Self {
-        // clone git repository using https url
-        if let Err(WorkflowError::IoError(message)) = Self::clone_repository(url, path_buf.clone())
-        {
-            println!("{}", message)
-        };
-        GitRepository {
+    /// Create a new GitRepository.
+    ///
+    /// Opens the checkout at `path_buf` if there is one, cloning `url` into
+    /// it otherwise. Fails if the checkout belongs to another remote or can
+    /// not be opened as a git repository.
+    pub fn new(url: &str, branch: &str, path_buf: PathBuf) -> WorkflowResult<Self> {
+        if Self::is_empty_dir(&path_buf) {
+            Self::clone_repository(url, branch, &path_buf)?;
+        } else {
+            Self::open_repository(url, &path_buf)?;
+        }
+        let id = id_from_url(url);
+        Ok(GitRepository {
             url: url.to_string(),
             branch: branch.to_string(),
             root: path_buf.clone(),
-            directory_repository: DirectoryRepository::new(path_buf),
+            commit: Self::head_commit(&path_buf),
+            directory_repository: DirectoryRepository::new(path_buf).with_id(&id),
+            id,
+        })
+    }
+
+    /// Use `id` instead of the name derived from the url to identify the
+    /// workflows of this repository.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self.directory_repository = self.directory_repository.with_id(&self.id);
+        self
+    }
+
+    fn head_commit(root: &Path) -> String {
+        Repository::open(root)
+            .and_then(|repo| {
+                let commit = repo.head()?.peel_to_commit()?;
+                Ok(commit.id().to_string())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record the checked out commit in the source of `workflow`
+    fn with_commit(&self, mut workflow: Workflow) -> Workflow {
+        if let Some(source) = workflow.source.as_mut() {
+            if !self.commit.is_empty() {
+                source.commit = Some(self.commit.clone());
+            }
+        }
+        workflow
+    }
+
+    fn is_empty_dir(path: &Path) -> bool {
+        match std::fs::read_dir(path) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(_) => !path.exists(),
         }
     }
 
-    fn clone_repository(url: &str, root: PathBuf) -> WorkflowResult<()> {
+    fn clone_repository(url: &str, branch: &str, root: &Path) -> WorkflowResult<()> {
         // clone the git repository
         let mut builder = RepoBuilder::new();
+        builder.branch(branch);
         builder.with_checkout(CheckoutBuilder::new());
 
-        let _ = builder
-            .clone(url, root.as_path())
-            .map_err(|_| WorkflowError::IoError("Unable to clone repo".to_string()));
+        builder.clone(url, root).map_err(|e| {
+            WorkflowError::git(root, format!("unable to clone {}: {}", url, e.message()))
+        })?;
         Ok(())
     }
+
+    fn open_repository(url: &str, root: &Path) -> WorkflowResult<()> {
+        let repo = Repository::open(root).map_err(|e| {
+            WorkflowError::git(root, format!("not a git checkout: {}", e.message()))
+        })?;
+        let origin = repo
+            .find_remote("origin")
+            .map_err(|e| WorkflowError::git(root, format!("no origin remote: {}", e.message())))?;
+        match origin.url() {
+            Some(origin_url) if origin_url == url => Ok(()),
+            other => Err(WorkflowError::Conflict(format!(
+                "{} is a checkout of {}, expected {}",
+                root.display(),
+                other.unwrap_or("<invalid url>"),
+                url
+            ))),
+        }
+    }
+
+    /// Fetch `branch` from `url`, check it out and fast-forward it to the
+    /// remote head. Local modifications inside the checkout are overwritten.
+    /// Returns the workflows whose files changed on disk.
+    pub fn pull(&mut self) -> WorkflowResult<ChangeSet> {
+        let changes = self.fast_forward().map_err(|e| match e {
+            WorkflowError::Git {
+                path: None,
+                message,
+            } => WorkflowError::git(&self.root, message),
+            other => other,
+        })?;
+        self.directory_repository.refresh()?;
+        Ok(changes)
+    }
+
+    fn fast_forward(&mut self) -> WorkflowResult<ChangeSet> {
+        let repo = Repository::open(&self.root)?;
+        let remote_ref = format!("refs/remotes/origin/{}", self.branch);
+
+        // fetch from the configured url, not whatever origin points to
+        let mut remote = repo.remote_anonymous(&self.url)?;
+        remote.fetch(
+            &[format!("+refs/heads/{}:{}", self.branch, remote_ref)],
+            None,
+            None,
+        )?;
+        let remote_commit = repo.find_reference(&remote_ref)?.peel_to_commit()?;
+
+        // remember what is on disk right now, to report changes later on
+        let old_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+        match repo.find_branch(&self.branch, BranchType::Local) {
+            Ok(branch) => {
+                let mut reference = branch.into_reference();
+                let local = reference.peel_to_commit()?;
+                if local.id() != remote_commit.id() {
+                    if !repo.graph_descendant_of(remote_commit.id(), local.id())? {
+                        return Err(WorkflowError::Conflict(format!(
+                            "{} has diverged from {}, unable to fast-forward",
+                            self.branch, self.url
+                        )));
+                    }
+                    reference.set_target(remote_commit.id(), "fast-forward")?;
+                }
+            }
+            Err(_) => {
+                repo.branch(&self.branch, &remote_commit, false)?;
+            }
+        }
+
+        repo.set_head(&format!("refs/heads/{}", self.branch))?;
+        repo.checkout_head(Some(CheckoutBuilder::new().force()))?;
+
+        let new_tree = remote_commit.tree()?;
+        let diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+        let id = |path: &Path| WorkflowId::for_file(&self.id, Path::new(""), path);
+        let mut changes = ChangeSet::default();
+        for delta in diff.deltas() {
+            let old_path = delta.old_file().path().filter(|p| is_workflow_file(p));
+            let new_path = delta.new_file().path().filter(|p| is_workflow_file(p));
+            match (delta.status(), old_path, new_path) {
+                (Delta::Added | Delta::Copied, _, Some(path)) => changes.added.push(id(path)),
+                (Delta::Deleted, Some(path), _) => changes.removed.push(id(path)),
+                (Delta::Modified | Delta::Typechange, _, Some(path)) => {
+                    changes.modified.push(id(path))
+                }
+                (Delta::Renamed, old, new) => {
+                    if let Some(path) = old {
+                        changes.removed.push(id(path));
+                    }
+                    if let Some(path) = new {
+                        changes.added.push(id(path));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.commit = remote_commit.id().to_string();
+        Ok(changes)
+    }
 }
 
 impl WorkflowRepository for GitRepository {
-    fn refresh(&mut self) -> WorkflowResult<()> {
-        // pull from git
-        // update directoryRepository
-        self.directory_repository.refresh()
+    fn id(&self) -> &str {
+        &self.id
     }
 
-    fn get_workflow(&self, name: &str) -> WorkflowResult<Workflow> {
-        self.directory_repository.get_workflow(name)
+    fn refresh(&mut self) -> WorkflowResult<ChangeSet> {
+        self.pull()
+    }
+
+    fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow> {
+        self.directory_repository
+            .get_workflow(id)
+            .map(|w| self.with_commit(w))
     }
 
     fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>> {
-        self.directory_repository.get_workflows()
+        let workflows = self.directory_repository.get_workflows()?;
+        Ok(workflows.into_iter().map(|w| self.with_commit(w)).collect())
     }
 
-    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<()> {
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<WorkflowId> {
         self.directory_repository.save_workflow(workflow)
     }
 
-    fn delete_workflow(&mut self, name: &str) -> WorkflowResult<()> {
-        self.directory_repository.delete_workflow(name)
+    fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()> {
+        self.directory_repository.delete_workflow(id)
+    }
+
+    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<WorkflowMatch>> {
+        let matches = self.directory_repository.query_workflows(query)?;
+        Ok(matches
+            .into_iter()
+            .map(|m| WorkflowMatch {
+                workflow: self.with_commit(m.workflow),
+                ..m
+            })
+            .collect())
     }
 
-    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<Workflow>> {
-        self.directory_repository.query_workflows(query)
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.directory_repository.diagnostics()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::workflow::repository::git::GitRepository;
+    use crate::prelude::WorkflowError;
+    use crate::workflow::repository::git::{id_from_url, GitRepository};
+    use crate::workflow::repository::ChangeSet;
     use crate::workflow::repository::WorkflowRepository;
-    use std::path::PathBuf;
+    use crate::workflow::source::WorkflowId;
+    use git2::{Oid, Repository, Signature};
+    use std::path::{Path, PathBuf};
+    use tempfile::TempDir;
+
+    /// Commit `files` onto `refs/heads/main` of a bare repository.
+    /// A `None` content removes the file.
+    fn commit(repo: &Repository, files: &[(&str, Option<&str>)]) -> Oid {
+        let parent = repo
+            .find_reference("refs/heads/main")
+            .and_then(|r| r.peel_to_commit())
+            .ok();
+        let parent_tree = parent.as_ref().map(|c| c.tree().unwrap());
+        let mut builder = repo.treebuilder(parent_tree.as_ref()).unwrap();
+        for (name, content) in files {
+            match content {
+                Some(content) => {
+                    let blob = repo.blob(content.as_bytes()).unwrap();
+                    builder.insert(name, blob, 0o100644).unwrap();
+                }
+                None => builder.remove(name).unwrap(),
+            }
+        }
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let signature = Signature::now("test", "test@example.com").unwrap();
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(
+            Some("refs/heads/main"),
+            &signature,
+            &signature,
+            "update workflows",
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    fn workflow_yaml(name: &str) -> String {
+        format!("---\nname: {}\ncommand: echo {}\n", name, name)
+    }
+
+    fn upstream(dir: &Path) -> Repository {
+        let repo = Repository::init_bare(dir.join("upstream.git")).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        commit(
+            &repo,
+            &[
+                ("a.yaml", Some(&workflow_yaml("a"))),
+                ("b.yaml", Some(&workflow_yaml("b"))),
+            ],
+        );
+        repo
+    }
 
     #[test]
+    #[ignore = "requires network access to github.com"]
     fn test_load_from_git() {
         let repo = GitRepository::new(
             "https://github.com/warpdotdev/workflows.git",
             "main",
             PathBuf::from("tests/fixtures/github/warpdotdev"),
-        );
+        )
+        .unwrap();
         assert_eq!(332, repo.get_workflows().unwrap().len());
     }
+
+    #[test]
+    fn test_pull_reports_changed_workflow_files() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+
+        let mut repo = GitRepository::new(&url, "main", dir.path().join("checkout")).unwrap();
+        assert_eq!(2, repo.get_workflows().unwrap().len());
+
+        commit(
+            &upstream,
+            &[
+                ("a.yaml", Some(&workflow_yaml("a2"))),
+                ("b.yaml", None),
+                ("c.yaml", Some(&workflow_yaml("c"))),
+                ("README.md", Some("not a workflow")),
+            ],
+        );
+
+        let changes = repo.pull().unwrap();
+        assert_eq!(
+            changes,
+            ChangeSet {
+                added: vec![WorkflowId::new("upstream", "c.yaml")],
+                modified: vec![WorkflowId::new("upstream", "a.yaml")],
+                removed: vec![WorkflowId::new("upstream", "b.yaml")],
+            }
+        );
+        let a = repo
+            .get_workflow(&WorkflowId::new("upstream", "a.yaml"))
+            .unwrap();
+        assert_eq!(a.name, "a2");
+        assert!(repo
+            .get_workflow(&WorkflowId::new("upstream", "b.yaml"))
+            .is_err());
+        assert_eq!(2, repo.get_workflows().unwrap().len());
+
+        assert!(repo.pull().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pull_checks_out_configured_branch() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+        let mut repo = GitRepository::new(&url, "main", dir.path().join("checkout")).unwrap();
+
+        let head = upstream.refname_to_id("refs/heads/main").unwrap();
+        let head = upstream.find_commit(head).unwrap();
+        upstream.branch("team", &head, false).unwrap();
+        repo.branch = "team".to_string();
+
+        assert!(repo.pull().unwrap().is_empty());
+        let checkout = Repository::open(dir.path().join("checkout")).unwrap();
+        assert_eq!(Some("refs/heads/team"), checkout.head().unwrap().name());
+    }
+
+    #[test]
+    fn test_new_opens_existing_checkout() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+        let checkout = dir.path().join("checkout");
+
+        GitRepository::new(&url, "main", checkout.clone()).unwrap();
+        let repo = GitRepository::new(&url, "main", checkout).unwrap();
+        assert_eq!(2, repo.get_workflows().unwrap().len());
+    }
+
+    #[test]
+    fn test_new_rejects_checkout_of_other_remote() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+        let checkout = dir.path().join("checkout");
+        GitRepository::new(&url, "main", checkout.clone()).unwrap();
+
+        match GitRepository::new("https://example.com/other.git", "main", checkout) {
+            Err(WorkflowError::Conflict(message)) => assert!(message.contains(&url)),
+            _ => panic!("expected a remote mismatch"),
+        }
+    }
+
+    #[test]
+    fn test_new_rejects_directory_that_is_no_checkout() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+        let checkout = dir.path().join("checkout");
+        std::fs::create_dir(&checkout).unwrap();
+        std::fs::write(checkout.join("a.yaml"), workflow_yaml("a")).unwrap();
+
+        assert!(matches!(
+            GitRepository::new(&url, "main", checkout),
+            Err(WorkflowError::Git { .. })
+        ));
+    }
+
+    #[test]
+    fn test_new_reports_clone_failure() {
+        let dir = TempDir::new().unwrap();
+        let missing = dir.path().join("missing.git").display().to_string();
+
+        assert!(matches!(
+            GitRepository::new(&missing, "main", dir.path().join("checkout")),
+            Err(WorkflowError::Git { .. })
+        ));
+    }
+
+    #[test]
+    fn test_workflows_carry_checked_out_commit() {
+        let dir = TempDir::new().unwrap();
+        let upstream = upstream(dir.path());
+        let url = upstream.path().display().to_string();
+        let mut repo = GitRepository::new(&url, "main", dir.path().join("checkout"))
+            .unwrap()
+            .with_id("team");
+
+        let head = commit(&upstream, &[("c.yaml", Some(&workflow_yaml("c")))]);
+        repo.pull().unwrap();
+
+        let c = repo
+            .get_workflow(&WorkflowId::new("team", "c.yaml"))
+            .unwrap();
+        assert_eq!(c.source.unwrap().commit, Some(head.to_string()));
+    }
+
+    #[test]
+    fn test_id_from_url() {
+        assert_eq!(
+            id_from_url("https://github.com/warpdotdev/workflows.git"),
+            "workflows"
+        );
+        assert_eq!(id_from_url("git@github.com:team/shared/"), "shared");
+        assert_eq!(id_from_url("/tmp/upstream.git"), "upstream");
+    }
 }
@@ -1,13 +1,76 @@
 use crate::prelude::WorkflowResult;
+use crate::workflow::diagnostic::Diagnostic;
 use crate::workflow::file_format::Workflow;
+use crate::workflow::search::WorkflowMatch;
+use crate::workflow::source::WorkflowId;
+pub mod composite;
 pub mod directory;
 pub mod git;
 
+/// Workflows added, changed or removed by a
+/// [`WorkflowRepository::refresh`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ChangeSet {
+    pub added: Vec<WorkflowId>,
+    pub modified: Vec<WorkflowId>,
+    pub removed: Vec<WorkflowId>,
+}
+
+impl ChangeSet {
+    /// The changes from the workflows `old` to the workflows `new`
+    pub fn between(old: &[Workflow], new: &[Workflow]) -> Self {
+        let find = |workflows: &[Workflow], id: &WorkflowId| {
+            workflows.iter().find(|w| w.id() == Some(id)).cloned()
+        };
+        let mut changes = ChangeSet::default();
+        for workflow in new {
+            let Some(id) = workflow.id() else { continue };
+            match find(old, id) {
+                None => changes.added.push(id.clone()),
+                Some(previous) if &previous != workflow => changes.modified.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        for workflow in old {
+            let Some(id) = workflow.id() else { continue };
+            if find(new, id).is_none() {
+                changes.removed.push(id.clone());
+            }
+        }
+        changes
+    }
+
+    pub fn extend(&mut self, other: ChangeSet) {
+        self.added.extend(other.added);
+        self.modified.extend(other.modified);
+        self.removed.extend(other.removed);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+}
+
 pub trait WorkflowRepository {
-    fn refresh(&mut self) -> WorkflowResult<()>;
-    fn get_workflow(&self, name: &str) -> WorkflowResult<Workflow>;
+    /// Unique id of this repository, the first part of every [`WorkflowId`]
+    fn id(&self) -> &str;
+    /// Load the workflows again, pulling remote sources first, and return
+    /// what changed
+    fn refresh(&mut self) -> WorkflowResult<ChangeSet>;
+    fn get_workflow(&self, id: &WorkflowId) -> WorkflowResult<Workflow>;
     fn get_workflows(&self) -> WorkflowResult<Vec<Workflow>>;
-    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<()>;
-    fn delete_workflow(&mut self, name: &str) -> WorkflowResult<()>;
-    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<Workflow>>;
+    /// Whether saved workflows are kept, checkouts of a remote are
+    /// overwritten on the next pull
+    fn is_writable(&self) -> bool {
+        true
+    }
+    /// Save `workflow`, over the file it was loaded from if that is part of
+    /// this repository, and return the id it was saved as
+    fn save_workflow(&mut self, workflow: Workflow) -> WorkflowResult<WorkflowId>;
+    fn delete_workflow(&mut self, id: &WorkflowId) -> WorkflowResult<()>;
+    /// Workflows fuzzily matching every term of `query`, best match first.
+    /// An empty query matches all workflows.
+    fn query_workflows(&self, query: &str) -> WorkflowResult<Vec<WorkflowMatch>>;
+    /// Problems found with the workflow files on the last refresh
+    fn diagnostics(&self) -> Vec<Diagnostic>;
 }
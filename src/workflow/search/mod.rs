@@ -0,0 +1,283 @@
+//! Fuzzy matching of workflows, loosely following the scoring of fzf:
+//! every query character has to appear in order, matches at word
+//! boundaries and runs of consecutive characters score higher, gaps
+//! between matched characters cost points.
+
+pub mod query;
+
+use crate::workflow::file_format::Workflow;
+use crate::workflow::search::query::Query;
+use std::cmp::Reverse;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL_CASE: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+/// The part of a workflow a query term matched
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field {
+    Name,
+    Description,
+    /// index into [`Workflow::tags`]
+    Tag(usize),
+    Command,
+    /// index into [`Workflow::arguments`]
+    Argument(usize),
+}
+
+impl Field {
+    /// How much a match in this field counts compared to the others
+    pub fn weight(&self) -> i64 {
+        match self {
+            Field::Name => 4,
+            Field::Tag(_) => 3,
+            Field::Argument(_) | Field::Command => 2,
+            Field::Description => 1,
+        }
+    }
+}
+
+/// Characters of a single field that matched, as char indices
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldMatch {
+    pub field: Field,
+    pub positions: Vec<usize>,
+}
+
+/// A workflow found by a query, with its score and the matched characters
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkflowMatch {
+    pub workflow: Workflow,
+    pub score: i64,
+    pub matches: Vec<FieldMatch>,
+}
+
+impl WorkflowMatch {
+    /// Matched character positions within `field`
+    pub fn positions(&self, field: Field) -> &[usize] {
+        self.matches
+            .iter()
+            .find(|m| m.field == field)
+            .map(|m| m.positions.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Score of a fuzzy match of a pattern in a text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn bonus(text: &[char], index: usize) -> i64 {
+    if index == 0 || !text[index - 1].is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if text[index - 1].is_lowercase() && text[index].is_uppercase() {
+        BONUS_CAMEL_CASE
+    } else {
+        0
+    }
+}
+
+/// Score matched `positions` of `text`: every matched character counts,
+/// characters at word boundaries and runs of characters count more,
+/// gaps between matched characters cost points.
+fn score(text: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    let mut run_bonus = 0;
+    let mut previous: Option<usize> = None;
+    for &i in positions {
+        let mut char_bonus = bonus(text, i);
+        match previous {
+            None => {
+                char_bonus *= 2;
+                run_bonus = char_bonus;
+            }
+            // a run keeps the bonus of its first character
+            Some(previous) if previous + 1 == i => {
+                char_bonus = char_bonus.max(run_bonus).max(BONUS_CONSECUTIVE);
+            }
+            Some(previous) => {
+                let gap = (i - previous - 1) as i64;
+                score -= PENALTY_GAP_START + PENALTY_GAP_EXTENSION * (gap - 1);
+                run_bonus = char_bonus;
+            }
+        }
+        score += SCORE_MATCH + char_bonus;
+        previous = Some(i);
+    }
+    score
+}
+
+/// Match `pattern` as a case-insensitive subsequence of `text`. Of all
+/// possible matches the shortest one ending at the first full match is
+/// scored, as fzf does for its fast path.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().map(lowercase).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: vec![],
+        });
+    }
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+
+    // find where the first complete match ends
+    let mut index = 0;
+    let end = lower.iter().position(|c| {
+        if *c == pattern[index] {
+            index += 1;
+        }
+        index == pattern.len()
+    })?;
+
+    // walk back to the latest possible start, giving the shortest window
+    let mut index = pattern.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if lower[i] == pattern[index - 1] {
+            index -= 1;
+            if index == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    for (i, c) in lower.iter().enumerate().take(end + 1).skip(start) {
+        if positions.len() < pattern.len() && *c == pattern[positions.len()] {
+            positions.push(i);
+        }
+    }
+    Some(FuzzyMatch {
+        score: score(&text, &positions),
+        positions,
+    })
+}
+
+/// Match `phrase` as a case-insensitive substring of `text`, scored like a
+/// fuzzy match without gaps
+pub fn phrase_match(phrase: &str, text: &str) -> Option<FuzzyMatch> {
+    let phrase: Vec<char> = phrase.chars().map(lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+    let start = if phrase.is_empty() {
+        0
+    } else {
+        lower.windows(phrase.len()).position(|w| w == phrase)?
+    };
+    let positions: Vec<usize> = (start..start + phrase.len()).collect();
+    Some(FuzzyMatch {
+        score: score(&text, &positions),
+        positions,
+    })
+}
+
+/// The searchable text of every field of `workflow`
+pub(crate) fn fields(workflow: &Workflow) -> Vec<(Field, &str)> {
+    let mut fields = vec![(Field::Name, workflow.name.as_str())];
+    if let Some(description) = &workflow.description {
+        fields.push((Field::Description, description.as_str()));
+    }
+    for (i, tag) in workflow.tags.iter().enumerate() {
+        fields.push((Field::Tag(i), tag.as_str()));
+    }
+    fields.push((Field::Command, workflow.command.as_str()));
+    for (i, argument) in workflow.arguments.iter().enumerate() {
+        fields.push((Field::Argument(i), argument.name.as_str()));
+    }
+    fields
+}
+
+/// Keep the workflows matching `query`, see [`Query`] for the syntax, best
+/// match first. Workflows with the same score keep their order.
+pub fn rank<'a>(
+    workflows: impl IntoIterator<Item = &'a Workflow>,
+    query: &str,
+) -> Vec<WorkflowMatch> {
+    let query = Query::parse(query);
+    let mut found: Vec<WorkflowMatch> = workflows
+        .into_iter()
+        .filter_map(|workflow| query.matches(workflow))
+        .collect();
+    sort(&mut found);
+    found
+}
+
+/// Sort matches by score, best first, keeping the order of equal scores
+pub fn sort(matches: &mut [WorkflowMatch]) {
+    matches.sort_by_key(|m| Reverse(m.score));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workflow::file_format::{Argument, Workflow};
+    use crate::workflow::search::{fuzzy_match, phrase_match, rank, Field};
+
+    #[test]
+    fn test_fuzzy_match_is_case_insensitive_subsequence() {
+        let found = fuzzy_match("GtCo", "git commit").unwrap();
+        assert_eq!(found.positions, vec![0, 2, 4, 5]);
+        assert!(fuzzy_match("gtx", "git commit").is_none());
+        assert_eq!(fuzzy_match("", "anything").unwrap().score, 0);
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_consecutive_and_boundaries() {
+        let consecutive = fuzzy_match("com", "git commit").unwrap();
+        let scattered = fuzzy_match("com", "create a random").unwrap();
+        assert!(consecutive.score > scattered.score);
+
+        let boundary = fuzzy_match("c", "git commit").unwrap();
+        let inner = fuzzy_match("c", "unlock").unwrap();
+        assert!(boundary.score > inner.score);
+    }
+
+    #[test]
+    fn test_fuzzy_match_uses_shortest_window() {
+        let found = fuzzy_match("ab", "a xx ab").unwrap();
+        assert_eq!(found.positions, vec![5, 6]);
+    }
+
+    #[test]
+    fn test_phrase_match_needs_contiguous_text() {
+        let found = phrase_match("Last Commit", "undo last commit").unwrap();
+        assert_eq!(found.positions, (5..16).collect::<Vec<usize>>());
+        assert!(phrase_match("undo commit", "undo last commit").is_none());
+    }
+
+    #[test]
+    fn test_rank_requires_all_terms() {
+        let workflow = Workflow::new("Undo last commit", "git reset HEAD~{{count}}")
+            .with_arguments(vec![Argument::new("count")]);
+
+        let found = rank([&workflow], "undo cnt");
+        assert_eq!(found[0].positions(Field::Name), &[0, 1, 2, 3]);
+        assert_eq!(found[0].positions(Field::Argument(0)), &[0, 3, 4]);
+        assert!(rank([&workflow], "undo docker").is_empty());
+    }
+
+    #[test]
+    fn test_rank_orders_by_score() {
+        let by_command = Workflow::new("List files", "ls -al | grep git");
+        let mut by_tag = Workflow::new("Show status", "status");
+        by_tag.tags = vec!["git".to_string()];
+        let by_name = Workflow::new("Git log", "git log");
+
+        let ranked = rank(&[by_command, by_tag, by_name], "git");
+        let names: Vec<&str> = ranked.iter().map(|m| m.workflow.name()).collect();
+        assert_eq!(names, vec!["Git log", "Show status", "List files"]);
+
+        assert_eq!(rank(&[Workflow::new("a", "b")], "").len(), 1);
+    }
+}
@@ -0,0 +1,381 @@
+use crate::workflow::file_format::Workflow;
+use crate::workflow::search::{
+    fields, fuzzy_match, phrase_match, Field, FieldMatch, FuzzyMatch, WorkflowMatch,
+};
+
+/// A single condition of a query
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    /// a word fuzzily matched against all fields
+    Text(String),
+    /// a quoted phrase, matched as a whole against all fields
+    Phrase(String),
+    /// `tag:git`, a tag equal to the value
+    Tag(String),
+    /// `shell:zsh`, a workflow for this shell or for any shell
+    Shell(String),
+    /// `author:name`, an author containing the value
+    Author(String),
+    /// `arg:url`, an argument with this name
+    Arg(String),
+}
+
+/// A parsed search query.
+///
+/// Terms separated by whitespace all have to match, `OR` separates
+/// alternatives and binds weaker than the implicit and, so
+/// `tag:git undo OR tag:docker` finds git workflows about undoing and all
+/// docker workflows. A `-` in front of a term negates it, `"..."` quotes a
+/// phrase or the value of a field like `author:"Jane Doe"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Query {
+    /// the empty query, matching everything
+    All,
+    Term(Term),
+    Not(Box<Query>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+}
+
+/// Score and matched characters of a query on one workflow
+#[derive(Default)]
+struct Hit {
+    score: i64,
+    matches: Vec<FieldMatch>,
+}
+
+impl Hit {
+    fn field(field: Field, found: FuzzyMatch) -> Self {
+        Hit {
+            score: found.score * field.weight(),
+            matches: vec![FieldMatch {
+                field,
+                positions: found.positions,
+            }],
+        }
+    }
+
+    /// Highlight all of `text` without adding to the score
+    fn whole(field: Field, text: &str) -> Self {
+        Hit {
+            score: 0,
+            matches: vec![FieldMatch {
+                field,
+                positions: (0..text.chars().count()).collect(),
+            }],
+        }
+    }
+
+    fn add(&mut self, other: Hit) {
+        self.score += other.score;
+        for found in other.matches {
+            match self.matches.iter_mut().find(|m| m.field == found.field) {
+                Some(existing) => {
+                    existing.positions.extend(found.positions);
+                    existing.positions.sort_unstable();
+                    existing.positions.dedup();
+                }
+                None => self.matches.push(found),
+            }
+        }
+    }
+}
+
+enum Token {
+    Or,
+    Term { negated: bool, term: Term },
+}
+
+/// Read a quoted string starting after the opening quote. A missing
+/// closing quote ends the string at the end of the input.
+fn quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut value = String::new();
+    for c in chars.by_ref() {
+        if c == '"' {
+            break;
+        }
+        value.push(c);
+    }
+    value
+}
+
+fn field_term(field: &str, value: String) -> Option<Term> {
+    match field.to_lowercase().as_str() {
+        "tag" => Some(Term::Tag(value)),
+        "shell" => Some(Term::Shell(value)),
+        "author" => Some(Term::Author(value)),
+        "arg" => Some(Term::Arg(value)),
+        _ => None,
+    }
+}
+
+fn tokenize(query: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut negated = false;
+        if c == '-' {
+            chars.next();
+            match chars.peek() {
+                Some(next) if !next.is_whitespace() => negated = true,
+                // a lone dash is searched for as is
+                _ => {
+                    tokens.push(Token::Term {
+                        negated,
+                        term: Term::Text("-".to_string()),
+                    });
+                    continue;
+                }
+            }
+        }
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let phrase = quoted(&mut chars);
+            if !phrase.is_empty() {
+                tokens.push(Token::Term {
+                    negated,
+                    term: Term::Phrase(phrase),
+                });
+            }
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut value = None;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            chars.next();
+            if c == '"' && word.ends_with(':') {
+                value = Some(quoted(&mut chars));
+                break;
+            }
+            word.push(c);
+        }
+
+        let term = match word.split_once(':') {
+            Some((field, rest)) => {
+                let value = value.unwrap_or_else(|| rest.to_string());
+                match field_term(field, value) {
+                    // `tag:` without a value yet, e.g. while typing
+                    Some(term) if term_value(&term).is_empty() => continue,
+                    Some(term) => term,
+                    None => Term::Text(word),
+                }
+            }
+            None if word == "OR" && !negated => {
+                tokens.push(Token::Or);
+                continue;
+            }
+            None => Term::Text(word),
+        };
+        tokens.push(Token::Term { negated, term });
+    }
+    tokens
+}
+
+fn term_value(term: &Term) -> &str {
+    match term {
+        Term::Text(value)
+        | Term::Phrase(value)
+        | Term::Tag(value)
+        | Term::Shell(value)
+        | Term::Author(value)
+        | Term::Arg(value) => value,
+    }
+}
+
+/// Find the best scoring field of `workflow` for `matcher`
+fn best_field(workflow: &Workflow, matcher: impl Fn(&str) -> Option<FuzzyMatch>) -> Option<Hit> {
+    fields(workflow)
+        .into_iter()
+        .filter_map(|(field, text)| matcher(text).map(|found| Hit::field(field, found)))
+        .max_by_key(|hit| hit.score)
+}
+
+impl Term {
+    fn eval(&self, workflow: &Workflow) -> Option<Hit> {
+        match self {
+            Term::Text(text) => best_field(workflow, |field| fuzzy_match(text, field)),
+            Term::Phrase(phrase) => best_field(workflow, |field| phrase_match(phrase, field)),
+            Term::Tag(tag) => workflow
+                .tags
+                .iter()
+                .position(|t| t.eq_ignore_ascii_case(tag))
+                .map(|i| Hit::whole(Field::Tag(i), tag)),
+            Term::Shell(shell) => {
+                let matches = workflow.shells.is_empty()
+                    || workflow
+                        .shells
+                        .iter()
+                        .any(|s| s.to_string().eq_ignore_ascii_case(shell));
+                matches.then(Hit::default)
+            }
+            Term::Author(author) => workflow
+                .author
+                .as_ref()
+                .filter(|a| a.to_lowercase().contains(&author.to_lowercase()))
+                .map(|_| Hit::default()),
+            Term::Arg(name) => workflow
+                .arguments
+                .iter()
+                .position(|a| a.name.eq_ignore_ascii_case(name))
+                .map(|i| Hit::whole(Field::Argument(i), name)),
+        }
+    }
+}
+
+impl Query {
+    /// Parse `query`. Parsing never fails, incomplete input like a missing
+    /// closing quote or a trailing `OR` is read as well as possible, so
+    /// queries can be evaluated while they are typed.
+    pub fn parse(query: &str) -> Self {
+        let mut alternatives = vec![];
+        let mut terms = vec![];
+        for token in tokenize(query) {
+            match token {
+                Token::Or => alternatives.push(std::mem::take(&mut terms)),
+                Token::Term { negated, term } => {
+                    let term = Query::Term(term);
+                    terms.push(if negated {
+                        Query::Not(Box::new(term))
+                    } else {
+                        term
+                    });
+                }
+            }
+        }
+        alternatives.push(terms);
+
+        let mut alternatives: Vec<Query> = alternatives
+            .into_iter()
+            .filter(|terms| !terms.is_empty())
+            .map(|mut terms| {
+                if terms.len() == 1 {
+                    terms.remove(0)
+                } else {
+                    Query::And(terms)
+                }
+            })
+            .collect();
+        match alternatives.len() {
+            0 => Query::All,
+            1 => alternatives.remove(0),
+            _ => Query::Or(alternatives),
+        }
+    }
+
+    fn eval(&self, workflow: &Workflow) -> Option<Hit> {
+        match self {
+            Query::All => Some(Hit::default()),
+            Query::Term(term) => term.eval(workflow),
+            Query::Not(query) => match query.eval(workflow) {
+                Some(_) => None,
+                None => Some(Hit::default()),
+            },
+            Query::And(queries) => {
+                let mut hit = Hit::default();
+                for query in queries {
+                    hit.add(query.eval(workflow)?);
+                }
+                Some(hit)
+            }
+            Query::Or(queries) => queries
+                .iter()
+                .filter_map(|query| query.eval(workflow))
+                .max_by_key(|hit| hit.score),
+        }
+    }
+
+    /// Evaluate the query on `workflow`
+    pub fn matches(&self, workflow: &Workflow) -> Option<WorkflowMatch> {
+        self.eval(workflow).map(|hit| WorkflowMatch {
+            workflow: workflow.clone(),
+            score: hit.score,
+            matches: hit.matches,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workflow::file_format::{Argument, Shell, Workflow};
+    use crate::workflow::search::query::{Query, Term};
+    use crate::workflow::search::Field;
+
+    fn text(value: &str) -> Query {
+        Query::Term(Term::Text(value.to_string()))
+    }
+
+    fn tag(value: &str) -> Query {
+        Query::Term(Term::Tag(value.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Query::parse("  "), Query::All);
+        assert_eq!(Query::parse("undo"), text("undo"));
+        assert_eq!(
+            Query::parse("tag:git undo OR -tag:docker"),
+            Query::Or(vec![
+                Query::And(vec![tag("git"), text("undo")]),
+                Query::Not(Box::new(tag("docker"))),
+            ])
+        );
+        assert_eq!(
+            Query::parse("\"last commit\" author:\"Jane Doe\" arg:url shell:zsh"),
+            Query::And(vec![
+                Query::Term(Term::Phrase("last commit".to_string())),
+                Query::Term(Term::Author("Jane Doe".to_string())),
+                Query::Term(Term::Arg("url".to_string())),
+                Query::Term(Term::Shell("zsh".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_incomplete_input() {
+        assert_eq!(Query::parse("git OR"), text("git"));
+        assert_eq!(Query::parse("tag:"), Query::All);
+        assert_eq!(
+            Query::parse("\"last com"),
+            Query::Term(Term::Phrase("last com".to_string()))
+        );
+        // unknown fields are searched for as text
+        assert_eq!(Query::parse("http://x"), text("http://x"));
+    }
+
+    #[test]
+    fn test_matches_fields() {
+        let mut workflow = Workflow::new("Undo last commit", "git reset HEAD~{{count}}")
+            .with_arguments(vec![Argument::new("count")]);
+        workflow.tags = vec!["git".to_string()];
+        workflow.author = Some("Jane Doe".to_string());
+        workflow.shells = vec![Shell::Zsh];
+
+        let found = Query::parse("tag:Git arg:count")
+            .matches(&workflow)
+            .unwrap();
+        assert_eq!(found.positions(Field::Tag(0)), &[0, 1, 2]);
+        assert_eq!(found.positions(Field::Argument(0)), &[0, 1, 2, 3, 4]);
+
+        assert!(Query::parse("author:jane shell:zsh")
+            .matches(&workflow)
+            .is_some());
+        assert!(Query::parse("shell:fish").matches(&workflow).is_none());
+        assert!(Query::parse("-tag:git").matches(&workflow).is_none());
+        assert!(Query::parse("tag:docker OR \"last commit\"")
+            .matches(&workflow)
+            .is_some());
+        assert!(Query::parse("\"commit last\"").matches(&workflow).is_none());
+
+        // workflows without shells run in any shell
+        workflow.shells.clear();
+        assert!(Query::parse("shell:fish").matches(&workflow).is_some());
+    }
+}
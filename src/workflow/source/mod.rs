@@ -0,0 +1,104 @@
+use crate::prelude::{WorkflowError, WorkflowResult};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// Identifies a workflow across all repositories by the id of the
+/// repository it lives in and its path relative to the repository root,
+/// e.g. `local:sub/demo3.yaml`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct WorkflowId {
+    pub repository: String,
+    pub path: String,
+}
+
+impl WorkflowId {
+    pub fn new(repository: impl Into<String>, path: impl Into<String>) -> Self {
+        WorkflowId {
+            repository: repository.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Build the id of `file` stored below `root`. Path separators are
+    /// normalized to `/` so ids look the same on every platform.
+    pub fn for_file(repository: impl Into<String>, root: &Path, file: &Path) -> Self {
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        let path = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        WorkflowId::new(repository, path)
+    }
+}
+
+impl Display for WorkflowId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.repository, self.path)
+    }
+}
+
+impl FromStr for WorkflowId {
+    type Err = WorkflowError;
+
+    fn from_str(s: &str) -> WorkflowResult<Self> {
+        match s.split_once(':') {
+            Some((repository, path)) if !repository.is_empty() && !path.is_empty() => {
+                Ok(WorkflowId::new(repository, path))
+            }
+            _ => Err(WorkflowError::InvalidArgument(format!(
+                "{} is not a workflow id, expected <repository>:<path>",
+                s
+            ))),
+        }
+    }
+}
+
+/// Where a workflow was loaded from: the repository and file it is stored
+/// in and, for git repositories, the commit that was checked out.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct WorkflowSource {
+    pub id: WorkflowId,
+    pub file: PathBuf,
+    pub commit: Option<String>,
+}
+
+impl WorkflowSource {
+    pub fn new(id: WorkflowId, file: PathBuf) -> Self {
+        WorkflowSource {
+            id,
+            file,
+            commit: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workflow::source::WorkflowId;
+    use std::path::Path;
+
+    #[test]
+    fn test_id_for_file_is_relative_to_root() {
+        let id = WorkflowId::for_file(
+            "local",
+            Path::new("tests/fixtures/workflows"),
+            Path::new("tests/fixtures/workflows/sub/demo3.yaml"),
+        );
+        assert_eq!(id, WorkflowId::new("local", "sub/demo3.yaml"));
+        assert_eq!(id.to_string(), "local:sub/demo3.yaml");
+    }
+
+    #[test]
+    fn test_parse_id() {
+        let id: WorkflowId = "workflows:specs/git/undo.yaml".parse().unwrap();
+        assert_eq!(id, WorkflowId::new("workflows", "specs/git/undo.yaml"));
+        assert!("no-separator".parse::<WorkflowId>().is_err());
+        assert!(":demo.yaml".parse::<WorkflowId>().is_err());
+    }
+}
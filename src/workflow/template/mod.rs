@@ -0,0 +1,177 @@
+use crate::prelude::{WorkflowError, WorkflowResult};
+use crate::workflow::file_format::Argument;
+
+pub mod quote;
+
+/// A piece of a workflow command
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// text copied as is
+    Text(&'a str),
+    /// a `{{name}}` placeholder, holding the trimmed name
+    Placeholder(&'a str),
+}
+
+/// Split `command` into text and `{{name}}` placeholders. Braces that do
+/// not hold a name, like `{{}}`, Go templates like `{{.Names}}` or an
+/// unclosed `{{`, are kept as text.
+pub fn tokenize(command: &str) -> Vec<Segment<'_>> {
+    scan(command).0
+}
+
+// the segments of `command` and the offset of a `{{` that is never closed
+fn scan(command: &str) -> (Vec<Segment<'_>>, Option<usize>) {
+    let mut segments = vec![];
+    let mut unclosed = None;
+    let mut text_start = 0;
+    let mut rest = 0;
+    while let Some(open) = command[rest..].find("{{").map(|i| rest + i) {
+        let Some(close) = command[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            unclosed = Some(open);
+            break;
+        };
+        let name = command[open + 2..close].trim();
+        if name.is_empty() || !name.chars().all(is_name_char) {
+            rest = open + 1;
+            continue;
+        }
+        if text_start < open {
+            segments.push(Segment::Text(&command[text_start..open]));
+        }
+        segments.push(Segment::Placeholder(name));
+        text_start = close + 2;
+        rest = text_start;
+    }
+    if text_start < command.len() {
+        segments.push(Segment::Text(&command[text_start..]));
+    }
+    (segments, unclosed)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Like `tokenize`, but a `{{` followed by a name that is never closed,
+/// like `{{url`, is an error instead of text
+pub fn parse(command: &str) -> WorkflowResult<Vec<Segment<'_>>> {
+    let (segments, unclosed) = scan(command);
+    if let Some(open) = unclosed {
+        let rest = command[open + 2..].trim_start();
+        let name: String = rest.chars().take_while(|c| is_name_char(*c)).collect();
+        if !name.is_empty() {
+            return Err(WorkflowError::Template(format!(
+                "unclosed placeholder {{{{{}",
+                name
+            )));
+        }
+    }
+    Ok(segments)
+}
+
+/// The names of the placeholders in `segments`, each once, in order
+pub fn placeholders<'a>(segments: &[Segment<'a>]) -> Vec<&'a str> {
+    let mut names = vec![];
+    for segment in segments {
+        if let Segment::Placeholder(name) = segment {
+            if !names.contains(name) {
+                names.push(*name);
+            }
+        }
+    }
+    names
+}
+
+/// Check that every placeholder has an argument and every argument is used
+pub fn check(segments: &[Segment], arguments: &[Argument]) -> WorkflowResult<()> {
+    let names = placeholders(segments);
+    let unknown: Vec<String> = names
+        .iter()
+        .filter(|name| !arguments.iter().any(|a| &a.name == *name))
+        .map(|name| format!("{{{{{}}}}}", name))
+        .collect();
+    let unused: Vec<&str> = arguments
+        .iter()
+        .map(|a| a.name.as_str())
+        .filter(|name| !names.contains(name))
+        .collect();
+    let mut problems = vec![];
+    if !unknown.is_empty() {
+        problems.push(format!("unknown placeholder(s) {}", unknown.join(", ")));
+    }
+    if !unused.is_empty() {
+        problems.push(format!("unused argument(s) {}", unused.join(", ")));
+    }
+    match problems.is_empty() {
+        true => Ok(()),
+        false => Err(WorkflowError::Template(problems.join("; "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::WorkflowError;
+    use crate::workflow::file_format::Argument;
+    use crate::workflow::template::{check, parse, tokenize, Segment};
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("curl -H \"{{header}}\" {{ url }}"),
+            vec![
+                Segment::Text("curl -H \""),
+                Segment::Placeholder("header"),
+                Segment::Text("\" "),
+                Segment::Placeholder("url"),
+            ]
+        );
+        assert_eq!(
+            tokenize("awk '{{}}' {{{name}}"),
+            vec![Segment::Text("awk '{{}}' {"), Segment::Placeholder("name"),]
+        );
+        assert_eq!(tokenize("echo {{"), vec![Segment::Text("echo {{")]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn test_go_templates_are_text() {
+        let command = "docker ps --format '{{.Names}} {{ json .Ports }}' -f name={{name}}";
+        assert_eq!(
+            parse(command).unwrap(),
+            vec![
+                Segment::Text("docker ps --format '{{.Names}} {{ json .Ports }}' -f name="),
+                Segment::Placeholder("name"),
+            ]
+        );
+        let segments = parse("kubectl get pods -o go-template='{{.metadata.name}}'").unwrap();
+        assert!(check(&segments, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_parse_reports_unclosed_placeholders() {
+        assert_eq!(
+            parse("echo {{ who }}").unwrap(),
+            vec![Segment::Text("echo "), Segment::Placeholder("who")]
+        );
+        assert!(parse("echo {{").is_ok());
+        assert!(parse("awk '{{}}'").is_ok());
+        let error = parse("curl {{url}} -H {{header").unwrap_err();
+        assert!(
+            matches!(error, WorkflowError::Template(m) if m == "unclosed placeholder {{header")
+        );
+    }
+
+    #[test]
+    fn test_check_placeholders_against_arguments() {
+        let arguments = vec![Argument::new("url"), Argument::new("header")];
+        let segments = parse("curl {{url}} {{url}}/a -H {{header}}").unwrap();
+        assert!(check(&segments, &arguments).is_ok());
+
+        let segments = parse("curl {{uri}} {{data}}").unwrap();
+        assert_eq!(
+            check(&segments, &arguments).unwrap_err().to_string(),
+            "invalid command template: unknown placeholder(s) {{uri}}, {{data}}; \
+             unused argument(s) url, header"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::workflow::file_format::Shell;
+
+/// The quotes a placeholder stands in when the command is parsed by a shell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Context {
+    Unquoted,
+    Single,
+    Double,
+}
+
+impl Context {
+    /// The context after the shell has read `text`
+    pub fn after(self, text: &str, shell: &Shell) -> Context {
+        let mut context = self;
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            context = match (context, c) {
+                (Context::Unquoted, '\'') => Context::Single,
+                (Context::Unquoted, '"') => Context::Double,
+                (Context::Single, '\'') | (Context::Double, '"') => Context::Unquoted,
+                // fish, unlike POSIX shells, allows \' and \\ in single quotes
+                (Context::Single, '\\') if *shell == Shell::Fish => {
+                    chars.next();
+                    context
+                }
+                (Context::Unquoted | Context::Double, '\\') => {
+                    chars.next();
+                    context
+                }
+                _ => context,
+            };
+        }
+        context
+    }
+}
+
+// characters that never need quoting in any of the supported shells
+fn is_safe(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "-_./:=@,+".contains(c)
+}
+
+// a word that every supported shell reads as it is, zsh expands a leading
+// `=` to the path of a command
+fn is_safe_word(value: &str) -> bool {
+    !value.is_empty() && !value.starts_with('=') && value.chars().all(is_safe)
+}
+
+fn escape(value: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape `value` so that `shell` reads it as a single word when it is
+/// inserted in `context`
+pub fn quote(value: &str, shell: &Shell, context: Context) -> String {
+    match (shell, context) {
+        (_, Context::Unquoted) if is_safe_word(value) => value.to_string(),
+        (Shell::Fish, Context::Unquoted) => format!("'{}'", escape(value, "\\'")),
+        (Shell::Fish, Context::Single) => escape(value, "\\'"),
+        (Shell::Fish, Context::Double) => escape(value, "\\$\""),
+        // a single quote cannot be escaped inside single quotes: close the
+        // quotes, add an escaped quote and open them again
+        (_, Context::Unquoted) => format!("'{}'", value.replace('\'', "'\\''")),
+        (_, Context::Single) => value.replace('\'', "'\\''"),
+        (_, Context::Double) => escape(value, "\\$`\""),
+    }
+}
+
+/// Like `quote`, but a leading `~` is kept out of the quotes so that the
+/// shell still expands it to the home directory
+pub fn quote_path(value: &str, shell: &Shell, context: Context) -> String {
+    match (context, value.strip_prefix('~')) {
+        (Context::Unquoted, Some("")) => "~".to_string(),
+        (Context::Unquoted, Some(rest)) if rest.starts_with('/') => {
+            format!("~{}", quote(rest, shell, context))
+        }
+        _ => quote(value, shell, context),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::workflow::file_format::Shell;
+    use crate::workflow::template::quote::{quote, quote_path, Context};
+
+    #[test]
+    fn test_quote_posix() {
+        let bash = Shell::Bash;
+        assert_eq!(quote("HEAD~1", &bash, Context::Unquoted), "'HEAD~1'");
+        assert_eq!(quote("a.b/c-d", &bash, Context::Unquoted), "a.b/c-d");
+        assert_eq!(quote("", &bash, Context::Unquoted), "''");
+        assert_eq!(
+            quote("it's $(rm)", &bash, Context::Unquoted),
+            "'it'\\''s $(rm)'"
+        );
+        assert_eq!(quote("it's", &bash, Context::Single), "it'\\''s");
+        assert_eq!(
+            quote("say \"$HOME\" `id` \\", &bash, Context::Double),
+            "say \\\"\\$HOME\\\" \\`id\\` \\\\"
+        );
+    }
+
+    #[test]
+    fn test_quote_zsh_equals() {
+        let zsh = Shell::Zsh;
+        assert_eq!(quote("=ls", &zsh, Context::Unquoted), "'=ls'");
+        assert_eq!(quote("key=value", &zsh, Context::Unquoted), "key=value");
+        assert_eq!(quote("=ls", &zsh, Context::Double), "=ls");
+    }
+
+    #[test]
+    fn test_quote_fish() {
+        let fish = Shell::Fish;
+        assert_eq!(quote("a b", &fish, Context::Unquoted), "'a b'");
+        assert_eq!(
+            quote("it's \\n", &fish, Context::Unquoted),
+            "'it\\'s \\\\n'"
+        );
+        assert_eq!(quote("it's", &fish, Context::Single), "it\\'s");
+        assert_eq!(quote("$(id) `x`", &fish, Context::Double), "\\$(id) `x`");
+    }
+
+    #[test]
+    fn test_quote_path_keeps_the_tilde() {
+        let bash = Shell::Bash;
+        assert_eq!(quote_path("~", &bash, Context::Unquoted), "~");
+        assert_eq!(
+            quote_path("~/My Documents", &bash, Context::Unquoted),
+            "~'/My Documents'"
+        );
+        assert_eq!(quote_path("~root", &bash, Context::Unquoted), "'~root'");
+        assert_eq!(quote_path("~/a b", &bash, Context::Double), "~/a b");
+    }
+
+    #[test]
+    fn test_context_after() {
+        let bash = Shell::Bash;
+        let fish = Shell::Fish;
+        let start = Context::Unquoted;
+        assert_eq!(start.after("curl -H \"", &bash), Context::Double);
+        assert_eq!(start.after("echo \"a\" 'b", &bash), Context::Single);
+        assert_eq!(start.after("echo \\\"", &bash), Context::Unquoted);
+        assert_eq!(start.after("echo '\\' ", &bash), Context::Unquoted);
+        assert_eq!(start.after("echo '\\' ", &fish), Context::Single);
+        assert_eq!(start.after("echo \"'", &bash), Context::Double);
+    }
+}